@@ -0,0 +1,542 @@
+//! read and write BibTeX
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use crate::config::BibEntry;
+
+/// an entry of a BibTeX file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibtexEntry {
+    /// entry type (e.g. `article`)
+    pub kind: String,
+    /// citation key
+    pub key: String,
+    /// fields with lowercase names
+    pub fields: HashMap<String, String>,
+}
+
+impl BibtexEntry {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// convert into a bibliography entry of `slide.toml`
+    pub fn to_bib_entry(&self) -> anyhow::Result<BibEntry> {
+        let Some(title) = self.field("title") else {
+            bail!("BibTeX entry `{}` has no title", self.key);
+        };
+        // a year such as `in press` or `2020a` is kept in the note
+        let mut note = self.field("note").map(String::from);
+        let year = self.field("year").and_then(|year| match year.parse() {
+            Ok(year) => Some(year),
+            Err(_) => {
+                log::warn!(
+                    "BibTeX entry `{}` has a non-numeric year: {year}; kept in the note",
+                    self.key
+                );
+                note = Some(match note.take() {
+                    Some(note) => format!("{note}, {year}"),
+                    None => year.to_string(),
+                });
+                None
+            }
+        });
+
+        let venue = ["journal", "booktitle", "school", "howpublished"]
+            .into_iter()
+            .find_map(|name| self.field(name))
+            .map(String::from);
+        let arxiv = self
+            .field("eprint")
+            .filter(|_| {
+                self.field("archiveprefix")
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case("arxiv"))
+            })
+            .map(String::from);
+        let field = |name: &str| self.field(name).map(String::from);
+
+        Ok(BibEntry {
+            tag: self.key.clone(),
+            kind: Some(self.kind.clone()),
+            title: title.to_string(),
+            authors: self.field("author").map(format_authors),
+            year,
+            month: field("month"),
+            venue,
+            volume: field("volume"),
+            pages: field("pages"),
+            publisher: field("publisher"),
+            doi: field("doi"),
+            arxiv,
+            note,
+            url: field("url"),
+        })
+    }
+}
+
+/// parse entries of a BibTeX file
+///
+/// `@string` macros are expanded; `@comment` and `@preamble` are skipped.
+pub fn parse_bibtex(input: &str) -> anyhow::Result<Vec<BibtexEntry>> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        strings: HashMap::new(),
+    };
+    let mut entries = vec![];
+
+    while parser.skip_to('@') {
+        parser.pos += 1;
+        let kind = parser.identifier().to_ascii_lowercase();
+        parser.skip_whitespace();
+
+        let Some(close) = parser.open_delimiter() else {
+            continue;
+        };
+
+        match kind.as_str() {
+            "comment" | "preamble" => {
+                parser.skip_balanced(close)?;
+            }
+            "string" => {
+                let (name, value) = parser.field()?;
+                parser.strings.insert(name, value);
+                parser.skip_balanced(close)?;
+            }
+            _ => {
+                parser.skip_whitespace();
+                let key = parser.until(&[',', close]).trim().to_string();
+                let mut fields = HashMap::new();
+
+                loop {
+                    parser.skip_whitespace();
+                    match parser.peek() {
+                        Some(',') => parser.pos += 1,
+                        Some(c) if c == close => {
+                            parser.pos += 1;
+                            break;
+                        }
+                        Some(_) => {
+                            let (name, value) = parser.field()?;
+                            fields.insert(name, value);
+                        }
+                        None => bail!("BibTeX entry `{key}` is not closed"),
+                    }
+                }
+
+                entries.push(BibtexEntry { kind, key, fields });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// format a bibliography entry as BibTeX
+pub fn to_bibtex(entry: &BibEntry) -> String {
+    let kind = entry.kind.as_deref().unwrap_or("misc");
+    let mut fields = vec![("title", entry.title.clone())];
+
+    if let Some(authors) = &entry.authors {
+        let authors = authors
+            .split(", ")
+            .flat_map(|author| author.split(" and "))
+            .collect::<Vec<_>>();
+        fields.push(("author", authors.join(" and ")));
+    }
+
+    if let Some(year) = entry.year {
+        fields.push(("year", year.to_string()));
+    }
+
+    if let Some(venue) = &entry.venue {
+        let name = match kind {
+            "article" => "journal",
+            "inproceedings" | "incollection" | "conference" => "booktitle",
+            _ => "howpublished",
+        };
+        fields.push((name, venue.clone()));
+    }
+
+    let optional = [
+        ("month", &entry.month),
+        ("volume", &entry.volume),
+        ("pages", &entry.pages),
+        ("publisher", &entry.publisher),
+        ("doi", &entry.doi),
+        ("eprint", &entry.arxiv),
+        ("note", &entry.note),
+        ("url", &entry.url),
+    ];
+    for (name, value) in optional {
+        if let Some(value) = value {
+            fields.push((name, value.clone()));
+        }
+    }
+    if entry.arxiv.is_some() {
+        fields.push(("archiveprefix", "arXiv".to_string()));
+    }
+
+    let fields = fields
+        .into_iter()
+        .map(|(name, value)| match name {
+            "doi" | "url" => (name, value),
+            _ => (name, escape_value(&value)),
+        })
+        .map(|(name, value)| format!("  {name} = {{{value}}}"))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!("@{kind}{{{},\n{}\n}}\n", entry.tag, fields)
+}
+
+/// `Last, First and Other, Name` -> `First Last, Name Other`
+fn format_authors(authors: &str) -> String {
+    authors
+        .split(" and ")
+        .map(str::trim)
+        .filter(|author| !author.is_empty())
+        .map(|author| match author.split_once(',') {
+            Some((last, first)) => format!("{} {}", first.trim(), last.trim()),
+            None => author.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// escape LaTeX special characters and dashes, the inverse of `clean_value`
+fn escape_value(value: &str) -> String {
+    value
+        .replace('&', "\\&")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+        .replace('$', "\\$")
+        .replace('#', "\\#")
+        .replace('—', "---")
+        .replace('–', "--")
+}
+
+/// remove braces and common LaTeX escapes
+fn clean_value(value: &str) -> String {
+    let value = value
+        .replace(['{', '}'], "")
+        .replace("\\&", "&")
+        .replace("\\%", "%")
+        .replace("\\_", "_")
+        .replace("\\$", "$")
+        .replace("\\#", "#")
+        .replace("---", "—")
+        .replace("--", "–")
+        .replace('~', " ");
+
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    strings: HashMap<String, String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// move to the next `target`; `false` at the end of input
+    fn skip_to(&mut self, target: char) -> bool {
+        while let Some(c) = self.peek() {
+            if c == target {
+                return true;
+            }
+            self.pos += 1;
+        }
+        false
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || "_-:.+/".contains(c))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn until(&mut self, stops: &[char]) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !stops.contains(&c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// consume `{` or `(` and return the closing delimiter
+    fn open_delimiter(&mut self) -> Option<char> {
+        let close = match self.peek()? {
+            '{' => '}',
+            '(' => ')',
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(close)
+    }
+
+    /// skip to after the `close` that ends the current entry
+    fn skip_balanced(&mut self, close: char) -> anyhow::Result<()> {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                c if c == close && depth == 0 => return Ok(()),
+                _ => {}
+            }
+        }
+        bail!("unexpected end of BibTeX input")
+    }
+
+    /// `name = value # value ...`
+    fn field(&mut self) -> anyhow::Result<(String, String)> {
+        self.skip_whitespace();
+        let name = self.identifier().to_ascii_lowercase();
+        self.skip_whitespace();
+        if self.peek() != Some('=') {
+            bail!("expected `=` after BibTeX field `{name}`");
+        }
+        self.pos += 1;
+
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            value.push_str(&self.value()?);
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        Ok((name, clean_value(&value)))
+    }
+
+    fn value(&mut self) -> anyhow::Result<String> {
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                let start = self.pos;
+                let mut depth = 0;
+                while let Some(c) = self.peek() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' if depth == 0 => {
+                            let value = self.chars[start..self.pos].iter().collect();
+                            self.pos += 1;
+                            return Ok(value);
+                        }
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+                bail!("unexpected end of BibTeX input")
+            }
+            Some('"') => {
+                self.pos += 1;
+                let start = self.pos;
+                let mut depth = 0;
+                while let Some(c) = self.peek() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        '"' if depth == 0 => {
+                            let value = self.chars[start..self.pos].iter().collect();
+                            self.pos += 1;
+                            return Ok(value);
+                        }
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+                bail!("unexpected end of BibTeX input")
+            }
+            _ => {
+                let token = self.identifier();
+                if token.is_empty() {
+                    bail!("unexpected character in BibTeX value");
+                }
+                Ok(self
+                    .strings
+                    .get(&token.to_ascii_lowercase())
+                    .cloned()
+                    .unwrap_or(token))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::BibEntry;
+
+    use super::{parse_bibtex, to_bibtex};
+
+    #[test]
+    fn test_parse_bibtex() {
+        let entries = parse_bibtex(
+            r#"
+% a comment line
+@string{neurips = "Advances in Neural Information Processing Systems"}
+
+@comment{ ignored {nested} }
+
+@inproceedings{vaswani2017,
+  title     = {Attention Is All You Need},
+  author    = {Vaswani, Ashish and Shazeer, Noam},
+  booktitle = neurips,
+  year      = 2017,
+  doi       = "10.5555/3295222.3295349",
+}
+
+@article(knuth84,
+  title = "Literate {P}rogramming",
+  author = "Donald E. Knuth",
+  journal = {The Computer Journal},
+  year = {1984},
+  pages = {97--111}
+)
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, "inproceedings");
+        assert_eq!(entries[1].fields["pages"], "97–111");
+
+        assert_eq!(
+            entries[0].to_bib_entry().unwrap(),
+            BibEntry {
+                tag: "vaswani2017".to_string(),
+                kind: Some("inproceedings".to_string()),
+                title: "Attention Is All You Need".to_string(),
+                authors: Some("Ashish Vaswani, Noam Shazeer".to_string()),
+                year: Some(2017),
+                venue: Some("Advances in Neural Information Processing Systems".to_string()),
+                doi: Some("10.5555/3295222.3295349".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            entries[1].to_bib_entry().unwrap(),
+            BibEntry {
+                tag: "knuth84".to_string(),
+                kind: Some("article".to_string()),
+                title: "Literate Programming".to_string(),
+                authors: Some("Donald E. Knuth".to_string()),
+                year: Some(1984),
+                venue: Some("The Computer Journal".to_string()),
+                pages: Some("97–111".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_non_numeric_year_is_kept_in_note() {
+        let entries = parse_bibtex(
+            "@article{a, title = {A}, year = {in press}}\n@article{b, title = {B}, year = {2020a}, note = {preprint}}\n",
+        )
+        .unwrap();
+
+        let a = entries[0].to_bib_entry().unwrap();
+        assert_eq!(a.year, None);
+        assert_eq!(a.note.as_deref(), Some("in press"));
+
+        let b = entries[1].to_bib_entry().unwrap();
+        assert_eq!(b.year, None);
+        assert_eq!(b.note.as_deref(), Some("preprint, 2020a"));
+    }
+
+    #[test]
+    fn test_bibtex_round_trip() {
+        let entry = BibEntry {
+            tag: "knuth84".to_string(),
+            kind: Some("misc".to_string()),
+            title: "Literate Programming".to_string(),
+            authors: Some("Donald E. Knuth, Someone Else".to_string()),
+            year: Some(1984),
+            venue: Some("The Computer Journal".to_string()),
+            url: Some("https://example.com/paper".to_string()),
+            ..Default::default()
+        };
+
+        let bibtex = to_bibtex(&entry);
+        let parsed = parse_bibtex(&bibtex).unwrap();
+
+        assert_eq!(
+            bibtex,
+            "@misc{knuth84,\n  title = {Literate Programming},\n  author = {Donald E. Knuth and Someone Else},\n  year = {1984},\n  howpublished = {The Computer Journal},\n  url = {https://example.com/paper}\n}\n"
+        );
+        assert_eq!(parsed[0].to_bib_entry().unwrap(), entry);
+
+        let article = BibEntry {
+            tag: "vaswani2017".to_string(),
+            kind: Some("article".to_string()),
+            title: "Attention Is All You Need".to_string(),
+            venue: Some("NeurIPS".to_string()),
+            volume: Some("30".to_string()),
+            arxiv: Some("1706.03762".to_string()),
+            note: Some("in press".to_string()),
+            ..Default::default()
+        };
+
+        let bibtex = to_bibtex(&article);
+        let parsed = parse_bibtex(&bibtex).unwrap();
+
+        assert!(bibtex.starts_with("@article{vaswani2017,\n"));
+        assert!(bibtex.contains("  journal = {NeurIPS},\n"));
+        assert!(!bibtex.contains("year"));
+        assert_eq!(parsed[0].to_bib_entry().unwrap(), article);
+    }
+
+    #[test]
+    fn test_bibtex_import_export_keeps_escapes() {
+        let source = r#"@article{fish,
+  title = {Fish \& Chips: 100\% of \#1 in \_snake\_case},
+  journal = {Food \& Drink},
+  year = {2020},
+  pages = {97--111},
+  url = {https://example.com/a_b}
+}
+"#;
+
+        let entry = parse_bibtex(source).unwrap()[0].to_bib_entry().unwrap();
+        assert_eq!(entry.title, "Fish & Chips: 100% of #1 in _snake_case");
+        assert_eq!(entry.pages.as_deref(), Some("97–111"));
+
+        let bibtex = to_bibtex(&entry);
+        assert!(bibtex.contains(r"  title = {Fish \& Chips: 100\% of \#1 in \_snake\_case},"));
+        assert!(bibtex.contains(r"  journal = {Food \& Drink},"));
+        assert!(bibtex.contains("  pages = {97--111},"));
+        assert!(bibtex.contains("  url = {https://example.com/a_b}"));
+        assert_eq!(
+            parse_bibtex(&bibtex).unwrap()[0].to_bib_entry().unwrap(),
+            entry
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! citation styles of in-text markers and footnotes
+
+use crate::config::{BibEntry, BibliographyConf, CitationStyle};
+
+/// text of the in-text marker (`[<marker>](#tag:page)`)
+pub fn citation_marker(conf: &BibliographyConf, entry: &BibEntry, number: usize) -> String {
+    let surnames = author_names(entry)
+        .into_iter()
+        .map(surname)
+        .collect::<Vec<_>>();
+
+    let year = entry.year_or_nd();
+
+    match conf.style {
+        CitationStyle::Plain | CitationStyle::Ieee => number.to_string(),
+        CitationStyle::AuthorYear => match surnames.as_slice() {
+            [] => format!("{} {year}", entry.title),
+            [one] => format!("{one} {year}"),
+            [first, second] => format!("{first} and {second} {year}"),
+            [first, ..] => format!("{first} et al. {year}"),
+        },
+        CitationStyle::Apa => match surnames.as_slice() {
+            [] => format!("{}, {year}", entry.title),
+            [one] => format!("{one}, {year}"),
+            [first, second] => format!("{first} & {second}, {year}"),
+            [first, ..] => format!("{first} et al., {year}"),
+        },
+        CitationStyle::Template => fill_template(
+            conf.marker_template.as_deref().unwrap_or("{n}"),
+            entry,
+            number,
+        ),
+    }
+}
+
+/// footnote line of the entry with its anchor
+pub fn citation_footnote(
+    conf: &BibliographyConf,
+    entry: &BibEntry,
+    number: usize,
+    anchor: &str,
+) -> String {
+    match conf.style {
+        CitationStyle::Plain => {
+            format!(
+                r#"<span id="{anchor}">[{number}]</span> {}"#,
+                entry.format()
+            )
+        }
+        CitationStyle::Ieee => {
+            format!(
+                r#"<span id="{anchor}">[{number}]</span> {}"#,
+                format_ieee(entry)
+            )
+        }
+        CitationStyle::AuthorYear => {
+            format!(
+                r#"<span id="{anchor}">{}</span>"#,
+                format_author_year(entry)
+            )
+        }
+        CitationStyle::Apa => format!(r#"<span id="{anchor}">{}</span>"#, format_apa(entry)),
+        CitationStyle::Template => format!(
+            r#"<span id="{anchor}">{}</span>"#,
+            fill_template(
+                conf.entry_template
+                    .as_deref()
+                    .unwrap_or("[{n}] {authors}. {title}. {venue}, {year}."),
+                entry,
+                number,
+            )
+        ),
+    }
+}
+
+/// `A. Author, B. Author, and C. Author, "Title," Venue, vol. 1, pp. 1–9, 2020. URL`
+fn format_ieee(entry: &BibEntry) -> String {
+    let authors = author_names(entry)
+        .into_iter()
+        .map(initialed)
+        .collect::<Vec<_>>();
+
+    let mut formatted = String::new();
+    if !authors.is_empty() {
+        formatted.push_str(&join_authors(&authors, ", ", " and ", ", and "));
+        formatted.push_str(", ");
+    }
+
+    let details = entry.details();
+    if details.is_empty() {
+        formatted.push_str(&format!("\"{}.\"", entry.title));
+    } else {
+        formatted.push_str(&format!("\"{},\" {}.", entry.title, details.join(", ")));
+    }
+    append_trailer(&mut formatted, entry);
+
+    formatted
+}
+
+/// `Authors. 2020. Title. Venue. URL`
+fn format_author_year(entry: &BibEntry) -> String {
+    let mut parts = vec![];
+    if let Some(authors) = &entry.authors {
+        parts.push(authors.clone());
+    }
+    parts.push(entry.year_or_nd());
+    parts.push(entry.title.clone());
+    parts.extend(entry.venue.clone());
+    parts.extend(entry.note.clone());
+
+    let mut formatted = parts.join(". ") + ".";
+    append_links(&mut formatted, entry);
+
+    formatted
+}
+
+/// `Author, A., & Other, B. (2020). Title. Venue. URL`
+fn format_apa(entry: &BibEntry) -> String {
+    let authors = author_names(entry)
+        .into_iter()
+        .map(|name| {
+            let initials = given_names(name)
+                .map(|given| format!("{}.", given.chars().next().unwrap_or_default()))
+                .collect::<Vec<_>>();
+            if initials.is_empty() {
+                surname(name).to_string()
+            } else {
+                format!("{}, {}", surname(name), initials.join(" "))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut formatted = String::new();
+    if !authors.is_empty() {
+        formatted.push_str(&join_authors(&authors, ", ", ", & ", ", & "));
+        formatted.push(' ');
+    }
+    formatted.push_str(&format!("({}). {}.", entry.year_or_nd(), entry.title));
+    if let Some(venue) = &entry.venue {
+        match (&entry.volume, &entry.pages) {
+            (Some(volume), Some(pages)) => {
+                formatted.push_str(&format!(" {venue}, {volume}, {pages}."))
+            }
+            (Some(volume), None) => formatted.push_str(&format!(" {venue}, {volume}.")),
+            (None, Some(pages)) => formatted.push_str(&format!(" {venue}, {pages}.")),
+            (None, None) => formatted.push_str(&format!(" {venue}.")),
+        }
+    }
+    if let Some(publisher) = &entry.publisher {
+        formatted.push_str(&format!(" {publisher}."));
+    }
+    append_trailer(&mut formatted, entry);
+
+    formatted
+}
+
+/// append the note, URL and identifier links
+fn append_trailer(formatted: &mut String, entry: &BibEntry) {
+    if let Some(note) = &entry.note {
+        formatted.push_str(&format!(" {note}."));
+    }
+    append_links(formatted, entry);
+}
+
+/// append the URL and DOI / arXiv links
+fn append_links(formatted: &mut String, entry: &BibEntry) {
+    for link in entry.url.iter().cloned().chain(entry.links()) {
+        formatted.push(' ');
+        formatted.push_str(&link);
+    }
+}
+
+/// replace `{n}`, `{tag}`, `{authors}`, `{surname}`, `{title}`, `{venue}`, `{year}`, `{url}`,
+/// `{doi}` and `{arxiv}`; identifiers are filled as links
+fn fill_template(template: &str, entry: &BibEntry, number: usize) -> String {
+    let surname = author_names(entry)
+        .first()
+        .map(|name| surname(name).to_string())
+        .unwrap_or_default();
+
+    template
+        .replace("{n}", &number.to_string())
+        .replace("{tag}", &entry.tag)
+        .replace("{authors}", entry.authors.as_deref().unwrap_or_default())
+        .replace("{surname}", &surname)
+        .replace("{title}", &entry.title)
+        .replace("{venue}", entry.venue.as_deref().unwrap_or_default())
+        .replace("{year}", &entry.year_or_nd())
+        .replace("{url}", entry.url.as_deref().unwrap_or_default())
+        .replace("{doi}", &entry.doi_link().unwrap_or_default())
+        .replace("{arxiv}", &entry.arxiv_link().unwrap_or_default())
+}
+
+/// names in `authors`, separated by `, ` or ` and `
+fn author_names(entry: &BibEntry) -> Vec<&str> {
+    entry
+        .authors
+        .as_deref()
+        .unwrap_or_default()
+        .split(", ")
+        .flat_map(|author| author.split(" and "))
+        .map(str::trim)
+        .filter(|author| !author.is_empty())
+        .collect()
+}
+
+fn surname(name: &str) -> &str {
+    name.split_whitespace().last().unwrap_or(name)
+}
+
+fn given_names(name: &str) -> impl Iterator<Item = &str> {
+    let tokens = name.split_whitespace().collect::<Vec<_>>();
+    let len = tokens.len().saturating_sub(1);
+    tokens.into_iter().take(len)
+}
+
+/// `Donald E. Knuth` -> `D. E. Knuth`
+fn initialed(name: &str) -> String {
+    given_names(name)
+        .map(|given| format!("{}.", given.chars().next().unwrap_or_default()))
+        .chain(std::iter::once(surname(name).to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn join_authors(authors: &[String], sep: &str, pair: &str, last: &str) -> String {
+    match authors {
+        [] => String::new(),
+        [one] => one.clone(),
+        [first, second] => format!("{first}{pair}{second}"),
+        [init @ .., tail] => format!("{}{last}{tail}", init.join(sep)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{BibEntry, BibliographyConf, CitationStyle};
+
+    use super::{citation_footnote, citation_marker};
+
+    fn entry(authors: Option<&str>) -> BibEntry {
+        BibEntry {
+            tag: "vaswani2017".to_string(),
+            title: "Attention Is All You Need".to_string(),
+            authors: authors.map(String::from),
+            year: Some(2017),
+            venue: Some("NeurIPS".to_string()),
+            url: None,
+            ..Default::default()
+        }
+    }
+
+    fn conf(style: CitationStyle) -> BibliographyConf {
+        BibliographyConf {
+            style,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_citation_markers() {
+        let two = entry(Some("Ashish Vaswani, Noam Shazeer"));
+        let three = entry(Some("Ashish Vaswani, Noam Shazeer, Niki Parmar"));
+
+        assert_eq!(citation_marker(&conf(CitationStyle::Plain), &two, 3), "3");
+        assert_eq!(
+            citation_marker(&conf(CitationStyle::AuthorYear), &two, 3),
+            "Vaswani and Shazeer 2017"
+        );
+        assert_eq!(
+            citation_marker(&conf(CitationStyle::AuthorYear), &three, 3),
+            "Vaswani et al. 2017"
+        );
+        assert_eq!(
+            citation_marker(&conf(CitationStyle::Apa), &two, 3),
+            "Vaswani & Shazeer, 2017"
+        );
+
+        let template = BibliographyConf {
+            style: CitationStyle::Template,
+            marker_template: Some("{surname}:{n}".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(citation_marker(&template, &three, 2), "Vaswani:2");
+    }
+
+    #[test]
+    fn test_citation_footnotes() {
+        let three = entry(Some("Ashish Vaswani, Noam Shazeer, Niki Parmar"));
+
+        assert_eq!(
+            citation_footnote(&conf(CitationStyle::Plain), &three, 1, "vaswani2017:2"),
+            r#"<span id="vaswani2017:2">[1]</span> Ashish Vaswani, Noam Shazeer, Niki Parmar. Attention Is All You Need. NeurIPS, 2017"#
+        );
+        assert_eq!(
+            citation_footnote(&conf(CitationStyle::Ieee), &three, 1, "vaswani2017:2"),
+            r#"<span id="vaswani2017:2">[1]</span> A. Vaswani, N. Shazeer, and N. Parmar, "Attention Is All You Need," NeurIPS, 2017."#
+        );
+        assert_eq!(
+            citation_footnote(&conf(CitationStyle::AuthorYear), &three, 1, "vaswani2017:2"),
+            r#"<span id="vaswani2017:2">Ashish Vaswani, Noam Shazeer, Niki Parmar. 2017. Attention Is All You Need. NeurIPS.</span>"#
+        );
+        assert_eq!(
+            citation_footnote(&conf(CitationStyle::Apa), &three, 1, "vaswani2017:2"),
+            r#"<span id="vaswani2017:2">Vaswani, A., Shazeer, N., & Parmar, N. (2017). Attention Is All You Need. NeurIPS.</span>"#
+        );
+    }
+
+    #[test]
+    fn test_citation_of_undated_entry_with_identifiers() {
+        let entry = BibEntry {
+            year: None,
+            doi: Some("10.5555/3295222.3295349".to_string()),
+            arxiv: Some("1706.03762".to_string()),
+            ..entry(Some("Ashish Vaswani"))
+        };
+
+        assert_eq!(
+            citation_marker(&conf(CitationStyle::Apa), &entry, 1),
+            "Vaswani, n.d."
+        );
+        assert_eq!(
+            citation_footnote(&conf(CitationStyle::Ieee), &entry, 1, "vaswani2017:1"),
+            r#"<span id="vaswani2017:1">[1]</span> A. Vaswani, "Attention Is All You Need," NeurIPS. [doi:10.5555/3295222.3295349](https://doi.org/10.5555/3295222.3295349) [arXiv:1706.03762](https://arxiv.org/abs/1706.03762)"#
+        );
+    }
+}
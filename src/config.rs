@@ -21,6 +21,18 @@ pub struct ProjectConf {
     /// image optimization configuration
     #[serde(default)]
     pub images: ImagesConf,
+    /// lint configuration
+    #[serde(default)]
+    pub lint: LintConf,
+    /// statistics configuration
+    #[serde(default)]
+    pub stats: StatsConf,
+    /// shared bibliography configuration
+    #[serde(default)]
+    pub bibliography: BibliographyConf,
+    /// slide numbering and table of contents configuration
+    #[serde(default)]
+    pub toc: TocConf,
 }
 
 impl Default for ProjectConf {
@@ -34,6 +46,10 @@ impl Default for ProjectConf {
             template: TemplateConf::default(),
             build: BuildConf::default(),
             images: ImagesConf::default(),
+            lint: LintConf::default(),
+            stats: StatsConf::default(),
+            bibliography: BibliographyConf::default(),
+            toc: TocConf::default(),
         }
     }
 }
@@ -68,6 +84,12 @@ pub struct ImagesConf {
     /// WebP handling configuration
     #[serde(default)]
     pub webp: WebpImageConf,
+    /// Mermaid diagram renderer configuration
+    #[serde(default)]
+    pub mermaid: MermaidImageConf,
+    /// Graphviz diagram renderer configuration
+    #[serde(default)]
+    pub dot: DotImageConf,
 }
 
 impl Default for ImagesConf {
@@ -82,6 +104,8 @@ impl Default for ImagesConf {
             jpeg: JpegImageConf::default(),
             svg: SvgImageConf::default(),
             webp: WebpImageConf::default(),
+            mermaid: MermaidImageConf::default(),
+            dot: DotImageConf::default(),
         }
     }
 }
@@ -151,18 +175,231 @@ impl Default for SvgImageConf {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WebpImageConf {
     #[serde(default)]
     pub enabled: bool,
 }
 
-impl Default for WebpImageConf {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MermaidImageConf {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_mmdc")]
+    pub tool: String,
+}
+
+impl Default for MermaidImageConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tool: default_mmdc(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DotImageConf {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_dot")]
+    pub tool: String,
+}
+
+impl Default for DotImageConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tool: default_dot(),
+        }
+    }
+}
+
+/// lint configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintConf {
+    /// warn when a referenced image is larger than this size (KiB)
+    #[serde(default = "default_max_image_size_kb")]
+    pub max_image_size_kb: u64,
+}
+
+impl Default for LintConf {
+    fn default() -> Self {
+        Self {
+            max_image_size_kb: default_max_image_size_kb(),
+        }
+    }
+}
+
+/// statistics configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsConf {
+    /// speaking rate for space separated words
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: u32,
+    /// speaking rate for CJK characters
+    #[serde(default = "default_characters_per_minute")]
+    pub characters_per_minute: u32,
+}
+
+impl Default for StatsConf {
+    fn default() -> Self {
+        Self {
+            words_per_minute: default_words_per_minute(),
+            characters_per_minute: default_characters_per_minute(),
+        }
+    }
+}
+
+/// bibliography configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BibliographyConf {
+    /// project bibliography relative to the project root (`.toml` or `.bib`)
+    #[serde(default)]
+    pub path: Option<String>,
+    /// where citations are numbered
+    #[serde(default)]
+    pub mode: BibliographyMode,
+    /// citation style of in-text markers and footnotes
+    #[serde(default)]
+    pub style: CitationStyle,
+    /// in-text marker for the `template` style (e.g. `{n}`)
+    #[serde(default)]
+    pub marker_template: Option<String>,
+    /// footnote entry for the `template` style (e.g. `[{n}] {authors}: {title} ({year})`)
+    #[serde(default)]
+    pub entry_template: Option<String>,
+    /// append a references page when no page has a `<!-- references -->` marker
+    #[serde(default)]
+    pub references_page: bool,
+    /// entries per references page (default 10)
+    #[serde(default)]
+    pub references_per_page: Option<usize>,
+    /// CSL-JSON resolver of DOIs and arXiv IDs (default `https://doi.org`)
+    #[serde(default)]
+    pub resolver: Option<String>,
+    /// CSL-JSON resolver of ISBNs, requested as `{isbn_resolver}/{isbn}`
+    #[serde(default)]
+    pub isbn_resolver: Option<String>,
+}
+
+impl BibliographyConf {
+    /// entries per references page, at least one
+    pub fn references_per_page(&self) -> usize {
+        self.references_per_page.unwrap_or(10).max(1)
+    }
+
+    /// base URL of the DOI resolver without a trailing slash
+    pub fn resolver(&self) -> &str {
+        self.resolver
+            .as_deref()
+            .unwrap_or("https://doi.org")
+            .trim_end_matches('/')
+    }
+}
+
+/// slide numbering and table of contents configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TocConf {
+    /// where titles are numbered
+    #[serde(default)]
+    pub mode: TocMode,
+    /// heading levels listed in the table of contents; 1 lists titles only
+    #[serde(default = "default_toc_depth")]
+    pub depth: usize,
+    /// list style of the table of contents
+    #[serde(default)]
+    pub numbering: TocNumbering,
+    /// list continuation pages (`Title (2/3)`) separately
+    #[serde(default)]
+    pub continuations: bool,
+    /// only the first title of each page is numbered and listed
+    #[serde(default)]
+    pub first_heading_only: bool,
+    /// number titles of appendix pages as `A1.`, `A2.`, …
+    #[serde(default)]
+    pub appendix_numbering: bool,
+    /// generate an agenda page before each section (needs `section_prefix`)
+    #[serde(default)]
+    pub agenda: bool,
+    /// title of agenda pages (default: `Agenda`)
+    pub agenda_title: Option<String>,
+}
+
+impl Default for TocConf {
     fn default() -> Self {
-        Self { enabled: false }
+        Self {
+            mode: TocMode::default(),
+            depth: default_toc_depth(),
+            numbering: TocNumbering::default(),
+            continuations: false,
+            first_heading_only: false,
+            appendix_numbering: false,
+            agenda: false,
+            agenda_title: None,
+        }
     }
 }
 
+impl TocConf {
+    /// title of agenda pages
+    pub fn agenda_title(&self) -> &str {
+        self.agenda_title.as_deref().unwrap_or("Agenda")
+    }
+}
+
+/// list style of the table of contents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TocNumbering {
+    /// Markdown ordered list (`1. Title`)
+    #[default]
+    Ordered,
+    /// Markdown bullet list (`- Title`)
+    Bullet,
+    /// explicit section numbers (`2. Method`, `2.1. Setup`)
+    Decimal,
+}
+
+/// where titles are numbered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TocMode {
+    /// `toc` appends `(i/n)` to titles in `slide.md`
+    #[default]
+    Source,
+    /// titles are numbered and `<!-- toc -->` is filled only in the build input
+    Build,
+}
+
+/// where citations are numbered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BibliographyMode {
+    /// `bib` rewrites citations and footnotes in `slide.md`
+    #[default]
+    Source,
+    /// citations are numbered only in the build input; `slide.md` keeps `[](#tag)`
+    Build,
+}
+
+/// citation style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CitationStyle {
+    /// `[1]` and `Authors. Title. Venue, Year. URL`
+    #[default]
+    Plain,
+    /// `[1]` and `A. Author and B. Author, "Title," Venue, Year.`
+    Ieee,
+    /// `Author et al. 2020` and `Authors. Year. Title. Venue.`
+    AuthorYear,
+    /// `Author & Other, 2020` and `Author, A., & Other, B. (2020). Title. Venue.`
+    Apa,
+    /// `marker_template` and `entry_template`
+    Template,
+}
+
 fn default_true() -> bool {
     true
 }
@@ -183,6 +420,14 @@ fn default_svgo() -> String {
     "svgo".to_string()
 }
 
+fn default_mmdc() -> String {
+    "mmdc".to_string()
+}
+
+fn default_dot() -> String {
+    "dot".to_string()
+}
+
 fn default_png_level() -> u8 {
     4
 }
@@ -191,6 +436,22 @@ fn default_jpeg_quality() -> u8 {
     85
 }
 
+fn default_max_image_size_kb() -> u64 {
+    1024
+}
+
+fn default_toc_depth() -> usize {
+    1
+}
+
+fn default_words_per_minute() -> u32 {
+    130
+}
+
+fn default_characters_per_minute() -> u32 {
+    300
+}
+
 /// template configuration
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TemplateConf {
@@ -237,21 +498,16 @@ impl Default for BuildConf {
 }
 
 /// strategy for published paths
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum PathStrategy {
+    #[default]
     Legacy,
     CanonicalWithRedirects,
 }
 
-impl Default for PathStrategy {
-    fn default() -> Self {
-        Self::Legacy
-    }
-}
-
 /// configuration for slide
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SlideConf {
     /// name of the slide
     pub name: String,
@@ -266,8 +522,16 @@ pub struct SlideConf {
     pub draft: Option<bool>,
     /// description of the slide
     pub description: Option<String>,
+    /// date the version was presented (YYYY-MM-DD)
+    pub presented_at: Option<String>,
+    /// event where the version was presented
+    pub event: Option<String>,
+    /// notes on the version (e.g. what changed for the talk)
+    pub notes: Option<String>,
     /// prefix of the title
     pub title_prefix: Option<String>,
+    /// prefix of section titles (e.g. `# ` with `title_prefix = "## "`)
+    pub section_prefix: Option<String>,
     /// type of slide.
     #[serde(rename = "type", default)]
     pub type_: SlideType,
@@ -277,6 +541,18 @@ pub struct SlideConf {
     pub path_strategy: Option<PathStrategy>,
 }
 
+impl SlideConf {
+    /// `event (presented_at)`, or whichever of them is recorded
+    pub fn presentation(&self) -> Option<String> {
+        match (&self.event, &self.presented_at) {
+            (Some(event), Some(date)) => Some(format!("{event} ({date})")),
+            (Some(event), None) => Some(event.clone()),
+            (None, Some(date)) => Some(date.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, ValueEnum)]
 pub enum SlideType {
     #[default]
@@ -302,18 +578,34 @@ impl SlideType {
 }
 
 /// bibliography entry
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct BibEntry {
     /// citation tag
     pub tag: String,
+    /// entry kind (e.g. `article`, `inproceedings`, `book`, `misc`)
+    pub kind: Option<String>,
     /// title of the reference
     pub title: String,
     /// authors of the reference
     pub authors: Option<String>,
-    /// year of the reference
-    pub year: u16,
+    /// year of the reference; `None` for undated or in-press references
+    pub year: Option<u16>,
+    /// month of the reference
+    pub month: Option<String>,
     /// conference or journal name
     pub venue: Option<String>,
+    /// volume of the journal or series
+    pub volume: Option<String>,
+    /// page range (e.g. `97–111`)
+    pub pages: Option<String>,
+    /// publisher
+    pub publisher: Option<String>,
+    /// DOI (e.g. `10.1093/comjnl/27.2.97`)
+    pub doi: Option<String>,
+    /// arXiv identifier (e.g. `1706.03762`)
+    pub arxiv: Option<String>,
+    /// free-form note (e.g. `in press`)
+    pub note: Option<String>,
     /// URL
     pub url: Option<String>,
 }
@@ -321,29 +613,77 @@ pub struct BibEntry {
 impl BibEntry {
     /// format bibliography entry as a string
     pub fn format(&self) -> String {
-        let mut entry = String::new();
+        let mut parts = vec![];
 
         if let Some(authors) = &self.authors {
-            entry.push_str(authors);
-            entry.push_str(". ");
+            parts.push(authors.clone());
         }
+        parts.push(self.title.clone());
 
-        entry.push_str(&self.title);
-        entry.push_str(". ");
-
-        if let Some(venue) = &self.venue {
-            entry.push_str(venue);
-            entry.push_str(", ");
+        let details = self.details();
+        if !details.is_empty() {
+            parts.push(details.join(", "));
         }
 
-        entry.push_str(&self.year.to_string());
+        parts.extend(self.note.clone());
+        parts.extend(self.url.clone());
+        parts.extend(self.links());
 
-        if let Some(url) = &self.url {
-            entry.push_str(". ");
-            entry.push_str(url);
+        parts.join(". ")
+    }
+
+    /// venue, volume, pages, publisher and date, in this order
+    pub fn details(&self) -> Vec<String> {
+        let mut details = vec![];
+
+        details.extend(self.venue.clone());
+        if let Some(volume) = &self.volume {
+            details.push(format!("vol. {volume}"));
+        }
+        if let Some(pages) = &self.pages {
+            details.push(format!("pp. {pages}"));
+        }
+        details.extend(self.publisher.clone());
+        if let Some(date) = self.date() {
+            details.push(date);
         }
 
-        entry
+        details
+    }
+
+    /// `Month Year`, `Year`, or `None` when undated
+    pub fn date(&self) -> Option<String> {
+        let year = self.year?;
+        Some(match &self.month {
+            Some(month) => format!("{month} {year}"),
+            None => year.to_string(),
+        })
+    }
+
+    /// year, or `n.d.` when undated
+    pub fn year_or_nd(&self) -> String {
+        self.year
+            .map_or_else(|| "n.d.".to_string(), |y| y.to_string())
+    }
+
+    /// DOI and arXiv identifiers as Markdown links
+    pub fn links(&self) -> Vec<String> {
+        self.doi_link()
+            .into_iter()
+            .chain(self.arxiv_link())
+            .collect()
+    }
+
+    /// `[doi:10.xxx](https://doi.org/10.xxx)`
+    pub fn doi_link(&self) -> Option<String> {
+        let doi = self.doi.as_ref()?;
+        Some(format!("[doi:{doi}](https://doi.org/{doi})"))
+    }
+
+    /// `[arXiv:1706.03762](https://arxiv.org/abs/1706.03762)`
+    pub fn arxiv_link(&self) -> Option<String> {
+        let id = self.arxiv.as_ref()?;
+        Some(format!("[arXiv:{id}](https://arxiv.org/abs/{id})"))
     }
 }
 
@@ -370,7 +710,7 @@ mod test_config {
             path_strategy = "legacy"
         "##;
 
-        let config: ProjectConf = toml::from_str(&config_example).unwrap();
+        let config: ProjectConf = toml::from_str(config_example).unwrap();
 
         println!("{:#?}", config);
         assert_eq!(config.build.path_strategy, PathStrategy::Legacy);
@@ -388,7 +728,7 @@ mod test_config {
             path_strategy = "canonical-with-redirects"
         "###;
 
-        let config: SlideConf = toml::from_str(&config_example).unwrap();
+        let config: SlideConf = toml::from_str(config_example).unwrap();
 
         println!("{:#?}", config);
         assert_eq!(
@@ -424,7 +764,7 @@ mod test_config {
             url = "https://doi.org/yyyy"
         "###;
 
-        let config: SlideConf = toml::from_str(&config_example).unwrap();
+        let config: SlideConf = toml::from_str(config_example).unwrap();
 
         println!("{:#?}", config);
 
@@ -440,6 +780,44 @@ mod test_config {
         assert_eq!(config.path_strategy, Some(PathStrategy::Legacy));
     }
 
+    #[test]
+    fn test_parse_bibliography_with_identifiers() {
+        let config_example = r###"
+            version = 1
+            name = "slide1"
+
+            [[bibliography]]
+            tag = "knuth84"
+            kind = "article"
+            authors = "Donald E. Knuth"
+            title = "Literate Programming"
+            year = 1984
+            venue = "The Computer Journal"
+            volume = "27"
+            pages = "97–111"
+            doi = "10.1093/comjnl/27.2.97"
+
+            [[bibliography]]
+            tag = "draft"
+            title = "A Draft"
+            arxiv = "2401.00001"
+            note = "in press"
+        "###;
+
+        let config: SlideConf = toml::from_str(config_example).unwrap();
+        let bibliography = config.bibliography.unwrap();
+
+        assert_eq!(
+            bibliography[0].format(),
+            "Donald E. Knuth. Literate Programming. The Computer Journal, vol. 27, pp. 97–111, 1984. [doi:10.1093/comjnl/27.2.97](https://doi.org/10.1093/comjnl/27.2.97)"
+        );
+        assert_eq!(bibliography[1].year, None);
+        assert_eq!(
+            bibliography[1].format(),
+            "A Draft. in press. [arXiv:2401.00001](https://arxiv.org/abs/2401.00001)"
+        );
+    }
+
     #[test]
     fn test_parse_legacy_configs_without_path_strategy() {
         let project_config = r##"
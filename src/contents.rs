@@ -1,12 +1,46 @@
 //! スライドの中身の操作
 
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
 use anyhow::bail;
 use itertools::Itertools;
 use regex::Regex;
 
-use crate::config::BibEntry;
+use crate::{
+    citation::{citation_footnote, citation_marker},
+    config::{BibEntry, BibliographyConf, CitationStyle},
+};
+
+/// citation written as `[](#tag)`, `[n](#tag)` or `[marker](#tag:page)`
+const CITATION: &str = r"\[([^\[\]]*)\]\(#([^\s)]*?)(:\d+)?\)";
+
+/// first link of a processed multi-citation, `[1](#a:2 "cite:a,b,c")`
+const CITATION_GROUP: &str = r#"\[([^\[\]]*)\]\(#([^\s)]*?)(:\d+) "cite:([^"]+)"\)"#;
+
+/// separators between the links of a multi-citation; empty links of the entries inside a
+/// compressed range follow the first link directly
+const CITATION_GROUP_SEPARATORS: [&str; 4] = ["–", ", ", "; ", ""];
+
+/// cross reference written as `[](#ref:label)` or `[n](#n "ref:label")`
+const CROSS_REFERENCE: &str = r#"\[\d*\]\((?:#ref:([^\s)]+)|#\d+ "ref:([^"]+)")\)"#;
+
+/// figure or table annotation `<!-- fig: id "caption" -->` with its generated caption
+const FIGURE_ANNOTATION: &str = r#"(?m)^<!--\s*(fig|tab):\s*([^\s"]+)(?:\s+"([^"]*)")?\s*-->(?:\n<div class="caption" id="[^"]*">.*</div>)?"#;
+
+/// figure or table reference written as `[](#fig:id)` or `[Fig. n](#fig:id)`
+const FIGURE_REFERENCE: &str = r"\[[^\]]*\]\(#(fig|tab):([^\s)]+)\)";
+
+/// marker of the reference list with its generated region
+const REFERENCES_MARKER: &str = r"(?s)<!--\s*references\s*-->(?:.*?<!--\s*/references\s*-->)?";
+
+/// marker of references pages generated for a long reference list
+const REFERENCES_CONTINUED: &str = "<!-- references: continued -->";
+
+/// separator page of the appendix; pages from it on are not indexed
+const APPENDIX_MARKER: &str = r"<!--\s*appendix\s*-->";
+
+/// anchor prefixes which are not citation keys
+const RESERVED_ANCHOR_PREFIXES: [&str; 3] = ["ref:", "fig:", "tab:"];
 
 /// contents of slide
 #[derive(Debug)]
@@ -15,23 +49,207 @@ pub struct SlideContents {
     pub frontmatter: String,
     /// Pages of slide
     pub pages: Vec<SlidePage>,
+    /// opening and closing separator lines of the frontmatter, if any
+    frontmatter_separators: Option<(String, String)>,
+    /// whitespace after the last page (e.g. the final newline)
+    ending: String,
 }
 
 impl SlideContents {
     /// update references in all pages
     pub fn modify_bibliography(&mut self, bib_entries: &[BibEntry]) {
+        self.modify_bibliography_with_style(bib_entries, &BibliographyConf::default())
+    }
+
+    /// update references in all pages with the citation style of `conf`
+    pub fn modify_bibliography_with_style(
+        &mut self,
+        bib_entries: &[BibEntry],
+        conf: &BibliographyConf,
+    ) {
+        self.prepare_references_pages(bib_entries, conf);
+
         let entries_each_page = self.enumerate_bib_entries(bib_entries);
         let bib_index = self.generate_bib_index(bib_entries);
 
-        for ((page, references), page_id) in self.pages.iter_mut().zip(entries_each_page).zip(1..) {
+        for ((page, references), page_id) in self.pages.iter_mut().zip(&entries_each_page).zip(1..)
+        {
             // update references
-            page.update_references(page_id, &bib_index);
+            page.update_references(page_id, &bib_index, conf);
 
             // update bibliography
-            page.update_bib_footnote(page_id, &references, &bib_index)
+            page.update_bib_footnote(page_id, references, &bib_index, conf)
+        }
+
+        self.fill_references_pages(&entries_each_page, &bib_index, conf);
+    }
+
+    /// rewrite the reference lists with the pages citing each entry, keeping the citations
+    ///
+    /// Used on the build input in the `source` mode, where pages may have moved since `bib`
+    /// wrote the lists (e.g. by includes or generated agenda pages).
+    pub fn update_citing_pages(&mut self, bib_entries: &[BibEntry], conf: &BibliographyConf) {
+        let entries_each_page = self.enumerate_bib_entries(bib_entries);
+        let bib_index = self.generate_bib_index(bib_entries);
+
+        self.fill_references_pages(&entries_each_page, &bib_index, conf);
+    }
+
+    /// drop generated references pages and insert as many as the reference list needs
+    fn prepare_references_pages(&mut self, bib_entries: &[BibEntry], conf: &BibliographyConf) {
+        self.pages
+            .retain(|page| !page.contents.contains(REFERENCES_CONTINUED));
+
+        let cited = self.generate_bib_index(bib_entries).len();
+        let marker_page = match self.pages.iter().position(SlidePage::has_references_marker) {
+            Some(index) => index,
+            None if conf.references_page && cited > 0 => {
+                self.pages
+                    .push(SlidePage::new("# References\n\n<!-- references -->"));
+                self.pages.len() - 1
+            }
+            None => return,
+        };
+
+        let heading = self.pages[marker_page]
+            .contents
+            .lines()
+            .find(|line| line.starts_with('#'))
+            .map(|line| format!("{} (cont.)\n\n", line.trim_end()))
+            .unwrap_or_default();
+
+        for index in 1..cited.div_ceil(conf.references_per_page()) {
+            self.pages.insert(
+                marker_page + index,
+                SlidePage::new(&format!(
+                    "{heading}{REFERENCES_CONTINUED}\n<!-- references -->"
+                )),
+            );
         }
     }
 
+    /// write the reference list with links to the citing pages into the references pages
+    fn fill_references_pages(
+        &mut self,
+        entries_each_page: &[Vec<&BibEntry>],
+        bib_index: &HashMap<&BibEntry, usize>,
+        conf: &BibliographyConf,
+    ) {
+        let mut citing_pages: HashMap<&BibEntry, Vec<usize>> = HashMap::new();
+        for (references, page_id) in entries_each_page.iter().zip(1..) {
+            for entry in references.iter().unique() {
+                citing_pages.entry(entry).or_default().push(page_id);
+            }
+        }
+
+        let items = bib_index
+            .iter()
+            .sorted_by_key(|(_, &number)| number)
+            .map(|(entry, &number)| {
+                let pages = citing_pages[entry]
+                    .iter()
+                    .map(|page_id| format!("[p. {page_id}](#{page_id})"))
+                    .join(", ");
+                format!(
+                    "{} (cited on {pages})",
+                    citation_footnote(conf, entry, number, &entry.tag)
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut chunks = items.chunks(conf.references_per_page());
+        for page in self
+            .pages
+            .iter_mut()
+            .filter(|page| page.has_references_marker())
+        {
+            page.update_references_list(chunks.next().unwrap_or_default());
+        }
+    }
+
+    /// update cross references (`[](#ref:label)`) in all pages
+    pub fn modify_cross_references(&mut self) -> anyhow::Result<()> {
+        let label_index = self.generate_label_index()?;
+
+        let unresolved = self
+            .pages
+            .iter()
+            .flat_map(|page| page.cross_references())
+            .filter(|label| !label_index.contains_key(*label))
+            .unique()
+            .collect::<Vec<_>>();
+        if !unresolved.is_empty() {
+            bail!("Unresolved labels: {}", unresolved.join(", "));
+        }
+
+        for page in self.pages.iter_mut() {
+            page.update_cross_references(&label_index);
+        }
+
+        Ok(())
+    }
+
+    /// generate index from label to page number
+    pub fn generate_label_index(&self) -> anyhow::Result<HashMap<String, usize>> {
+        let mut label_index = HashMap::new();
+
+        for (page, page_id) in self.pages.iter().zip(1..) {
+            for label in page.annotations("label") {
+                if let Some(other) = label_index.insert(label.to_string(), page_id) {
+                    bail!("Duplicate label `{label}` on pages {other} and {page_id}");
+                }
+            }
+        }
+
+        Ok(label_index)
+    }
+
+    /// number figures and tables, and update their captions and references
+    pub fn modify_figures(&mut self) -> anyhow::Result<()> {
+        let figure_index = self.generate_figure_index()?;
+
+        let unresolved = self
+            .pages
+            .iter()
+            .flat_map(|page| page.figure_references())
+            .filter(|anchor| !figure_index.contains_key(anchor))
+            .unique()
+            .collect::<Vec<_>>();
+        if !unresolved.is_empty() {
+            bail!("Unresolved figures: {}", unresolved.join(", "));
+        }
+
+        for page in self.pages.iter_mut() {
+            page.update_figure_captions(&figure_index);
+            page.update_figure_references(&figure_index);
+        }
+
+        Ok(())
+    }
+
+    /// generate index from figure anchor (e.g. `fig:arch`) to its label (e.g. `Fig. 1`)
+    pub fn generate_figure_index(&self) -> anyhow::Result<HashMap<String, String>> {
+        let re = Regex::new(FIGURE_ANNOTATION).unwrap();
+        let mut figure_index = HashMap::new();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+
+        for page in &self.pages {
+            for caps in re.captures_iter(&page.contents) {
+                let kind = &caps[1];
+                let anchor = format!("{kind}:{}", &caps[2]);
+                let count = counts.entry(kind.to_string()).or_default();
+                *count += 1;
+
+                let label = format!("{} {count}", figure_kind_label(kind));
+                if figure_index.insert(anchor.clone(), label).is_some() {
+                    bail!("Duplicate figure `{anchor}`");
+                }
+            }
+        }
+
+        Ok(figure_index)
+    }
+
     /// generate inverted index of bibliography entries
     pub fn generate_bib_index<'a>(
         &self,
@@ -59,97 +277,250 @@ impl SlideContents {
     }
 
     /// encode to marp
+    ///
+    /// Separators and blank lines around the pages are kept from the source; pages added
+    /// since are separated by `---` and a blank line.
     pub fn to_marp(&self) -> String {
         let mut s = String::new();
-        s += "---\n";
-        s += &self.frontmatter;
-        s += "---\n";
+        if let Some((opening, closing)) = &self.frontmatter_separators {
+            s += opening;
+            s += &self.frontmatter;
+            s += closing;
+        }
 
-        s += &self
-            .pages
-            .iter()
-            .map(|p| p.contents.trim())
-            .join("\n\n---\n\n");
+        for (index, page) in self.pages.iter().enumerate() {
+            if index > 0 {
+                s += &page.separator;
+            }
+            s += &page.before;
+            s += page.contents.trim();
+            if index + 1 < self.pages.len() {
+                s += &page.after;
+            }
+        }
+        s += &self.ending;
 
         s
     }
-}
 
-impl TryFrom<&str> for SlideContents {
-    type Error = anyhow::Error;
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
+    /// parse contents whose frontmatter may be missing, taking it as an empty one
+    pub fn parse_without_frontmatter_check(value: &str) -> anyhow::Result<Self> {
+        Self::parse(value, false)
+    }
+
+    fn parse(value: &str, frontmatter_required: bool) -> anyhow::Result<Self> {
         let separator = Regex::new(r"^-{3,}$").unwrap();
+        let is_separator = |line: &str| separator.is_match(line.trim_end_matches(['\n', '\r']));
 
-        let mut splitted = vec![];
+        let has_frontmatter = value.split_inclusive('\n').next().is_some_and(is_separator);
+        if frontmatter_required && !has_frontmatter {
+            bail!("Frontmatter is missing");
+        }
 
-        for line in value.lines() {
-            if separator.is_match(line) {
-                splitted.push(String::default());
-            } else {
+        // (separator line, raw contents) of each part
+        let mut splitted: Vec<(String, String)> = vec![];
+        if !has_frontmatter {
+            splitted.push(Default::default());
+        }
+        let mut fences = Fences::default();
+
+        for line in value.split_inclusive('\n') {
+            fences.toggles(line);
+            if !fences.is_open() && is_separator(line) {
+                splitted.push((line.to_string(), String::default()));
+            } else if let Some((_, last)) = splitted.last_mut() {
                 // 末尾の文字列に追加
-                if let Some(last) = splitted.last_mut() {
-                    *last += line;
-                    *last += "\n";
-                } else {
-                    bail!("Frontmatter is missing");
-                }
+                *last += line;
             }
         }
 
-        if splitted.len() < 2 {
-            bail!("Frontmatter is missing");
-        }
+        let frontmatter = if has_frontmatter {
+            if splitted.len() < 2 {
+                bail!("Frontmatter is missing");
+            }
+            Some(splitted.remove(0))
+        } else {
+            None
+        };
+
+        let mut pages = splitted
+            .into_iter()
+            .map(|(separator, raw)| {
+                let contents = raw.trim();
+                let start = raw.len() - raw.trim_start().len();
+                SlidePage {
+                    contents: contents.to_string(),
+                    separator,
+                    before: raw[..start].to_string(),
+                    after: raw[start + contents.len()..].to_string(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // the first page follows the closing separator of the frontmatter
+        let (frontmatter, frontmatter_separators) = match frontmatter {
+            Some((opening, frontmatter)) => {
+                let closing = std::mem::replace(&mut pages[0].separator, "---\n".to_string());
+                (frontmatter, Some((opening, closing)))
+            }
+            None => (String::default(), None),
+        };
+
+        let ending = pages
+            .last_mut()
+            .map(|page| std::mem::replace(&mut page.after, "\n\n".to_string()))
+            .unwrap_or_default();
 
         Ok(Self {
-            frontmatter: splitted.remove(0),
-            pages: splitted
-                .into_iter()
-                .map(|s| SlidePage {
-                    contents: s.trim().to_string(),
-                })
-                .collect(),
+            frontmatter,
+            pages,
+            frontmatter_separators,
+            ending,
+        })
+    }
+
+    /// index of the `<!-- appendix -->` separator page
+    pub fn appendix_start(&self) -> Option<usize> {
+        self.pages.iter().position(SlidePage::is_appendix_separator)
+    }
+
+    /// pages before the appendix separator
+    pub fn main_pages(&self) -> &[SlidePage] {
+        &self.pages[..self.appendix_start().unwrap_or(self.pages.len())]
+    }
+
+    /// get a value of the frontmatter (e.g. `theme`)
+    pub fn frontmatter_value(&self, key: &str) -> Option<&str> {
+        self.frontmatter.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim().trim_matches(['"', '\'']))
         })
     }
 }
 
+impl TryFrom<&str> for SlideContents {
+    type Error = anyhow::Error;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value, true)
+    }
+}
+
 /// Pages of slide
 #[derive(Debug)]
 pub struct SlidePage {
     /// Contents of page
     contents: String,
+    /// separator line before the page
+    separator: String,
+    /// whitespace between the separator and the contents
+    before: String,
+    /// whitespace between the contents and the next separator
+    after: String,
 }
 
 impl SlidePage {
-    /// enumerate references in the page
-    pub fn enumerate_references<'a>(&self, bib_entries: &'a [BibEntry]) -> Vec<&'a BibEntry> {
-        let re = Regex::new(r"\[\d*?\]\(#(.*?)(|:\d+)\)").unwrap();
+    /// page with the contents
+    pub fn new(contents: &str) -> Self {
+        Self {
+            contents: contents.trim().to_string(),
+            separator: "---\n".to_string(),
+            before: "\n".to_string(),
+            after: "\n\n".to_string(),
+        }
+    }
 
-        // collect keys from the page
-        let keys: Vec<&str> = re
-            .captures_iter(&self.contents)
-            .filter_map(|cap| cap.get(1).map(|m| m.as_str()))
-            .collect();
+    /// replace the contents, keeping the separator and blank lines around them
+    pub fn set_contents(&mut self, contents: &str) {
+        self.contents = contents.trim().to_string();
+    }
 
-        // find corresponding BibEntry for each key
-        keys.iter()
-            .filter_map(|&key| bib_entries.iter().find(|entry| entry.tag == key))
+    /// contents of the page
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    /// title of the page (first line starting with `title_prefix`)
+    pub fn title(&self, title_prefix: &str) -> Option<&str> {
+        self.markdown_lines()
+            .into_iter()
+            .find(|(_, line)| line.starts_with(title_prefix))
+            .map(|(_, line)| line.trim_start_matches(title_prefix).trim())
+    }
+
+    /// whether the page has the `<!-- appendix -->` marker
+    pub fn is_appendix_separator(&self) -> bool {
+        let re = Regex::new(APPENDIX_MARKER).unwrap();
+
+        let mut fences = Fences::default();
+        self.contents.lines().any(|line| {
+            fences.toggles(line);
+            !fences.is_open() && re.is_match(line)
+        })
+    }
+
+    /// lines outside fenced code blocks and HTML comments with their line indices
+    pub fn markdown_lines(&self) -> Vec<(usize, &str)> {
+        let mut fences = Fences::default();
+        let mut in_comment = false;
+        let mut lines = vec![];
+
+        for (i, line) in self.contents.lines().enumerate() {
+            if in_comment {
+                in_comment = !line.contains("-->");
+                continue;
+            }
+            if fences.toggles(line) {
+                continue;
+            }
+            if fences.is_open() {
+                continue;
+            }
+            if let Some(start) = line.rfind("<!--") {
+                in_comment = !line[start..].contains("-->");
+            }
+            if !line.trim_start().starts_with("<!--") {
+                lines.push((i, line));
+            }
+        }
+
+        lines
+    }
+
+    /// value of an annotation comment like `<!-- key: value -->`
+    pub fn annotation(&self, key: &str) -> Option<&str> {
+        self.annotations(key).into_iter().next()
+    }
+
+    /// values of all annotation comments with the key
+    pub fn annotations(&self, key: &str) -> Vec<&str> {
+        let re = Regex::new(&format!(r"<!--\s*{}:\s*(.*?)\s*-->", regex::escape(key))).unwrap();
+
+        re.captures_iter(&self.contents)
+            .filter_map(|cap| cap.get(1))
+            .map(|m| m.as_str())
+            .collect()
+    }
+
+    /// labels referenced in the page by `[](#ref:label)`
+    pub fn cross_references(&self) -> Vec<&str> {
+        let re = Regex::new(CROSS_REFERENCE).unwrap();
+
+        re.captures_iter(&self.contents)
+            .filter_map(|cap| cap.get(1).or_else(|| cap.get(2)))
+            .map(|m| m.as_str())
             .unique()
             .collect()
     }
 
-    /// update reference item
-    pub fn update_references(&mut self, page_id: usize, bib_index: &HashMap<&BibEntry, usize>) {
-        let re = Regex::new(r"\[\d*?\]\(#(.*?)(|:\d+)\)").unwrap();
+    /// update cross references to the page number of each label
+    pub fn update_cross_references(&mut self, label_index: &HashMap<String, usize>) {
+        let re = Regex::new(CROSS_REFERENCE).unwrap();
 
         let new_contents = re
             .replace_all(&self.contents, |caps: &regex::Captures| {
-                let key = &caps[1];
-                if let Some(entry) = bib_index.keys().find(|e| e.tag == key) {
-                    if let Some(&idx) = bib_index.get(entry) {
-                        format!("[{idx}](#{key}:{page_id})")
-                    } else {
-                        caps[0].to_string()
-                    }
+                let label = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+                if let Some(page_id) = label_index.get(label) {
+                    format!(r#"[{page_id}](#{page_id} "ref:{label}")"#)
                 } else {
                     caps[0].to_string()
                 }
@@ -159,18 +530,206 @@ impl SlidePage {
         self.contents = new_contents;
     }
 
+    /// number of fenced code blocks
+    pub fn code_block_count(&self) -> usize {
+        let mut fences = Fences::default();
+        self.contents
+            .lines()
+            .filter(|line| fences.toggles(line))
+            .count()
+            / 2
+    }
+
+    /// text read aloud: without code blocks, comments, tags, images and link targets
+    pub fn spoken_text(&self) -> String {
+        let comment = Regex::new(r"(?s)<!--.*?-->").unwrap();
+        let tag = Regex::new(r"<[^>]+>").unwrap();
+        let image = Regex::new(r"!\[[^\]]*\]\([^)]*\)").unwrap();
+        let link = Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap();
+        let markup = Regex::new(r"[#*_`>|~]|^\s*(?:[-+]|\d+\.)\s").unwrap();
+
+        let mut fences = Fences::default();
+        let text = self
+            .contents
+            .lines()
+            .filter(|line| {
+                if fences.toggles(line) {
+                    return false;
+                }
+                !fences.is_open()
+            })
+            .join("\n");
+
+        let text = comment.replace_all(&text, "");
+        let text = tag.replace_all(&text, " ");
+        let text = image.replace_all(&text, "");
+        let text = link.replace_all(&text, "$1");
+
+        text.lines()
+            .map(|line| markup.replace_all(line, "").to_string())
+            .join("\n")
+    }
+
+    /// figure anchors referenced in the page by `[](#fig:id)` or `[](#tab:id)`
+    pub fn figure_references(&self) -> Vec<String> {
+        let re = Regex::new(FIGURE_REFERENCE).unwrap();
+
+        re.captures_iter(&self.contents)
+            .map(|cap| format!("{}:{}", &cap[1], &cap[2]))
+            .unique()
+            .collect()
+    }
+
+    /// put a numbered caption after each figure annotation
+    pub fn update_figure_captions(&mut self, figure_index: &HashMap<String, String>) {
+        let re = Regex::new(FIGURE_ANNOTATION).unwrap();
+
+        let new_contents = re
+            .replace_all(&self.contents, |caps: &regex::Captures| {
+                let anchor = format!("{}:{}", &caps[1], &caps[2]);
+                let Some(label) = figure_index.get(&anchor) else {
+                    return caps[0].to_string();
+                };
+                let comment = caps[0].lines().next().unwrap_or_default();
+                let caption = match caps.get(3).map(|m| m.as_str()) {
+                    Some(caption) if !caption.is_empty() => format!("{label}: {caption}"),
+                    _ => label.clone(),
+                };
+
+                format!(
+                    r#"{comment}
+<div class="caption" id="{anchor}">{caption}</div>"#
+                )
+            })
+            .to_string();
+
+        self.contents = new_contents;
+    }
+
+    /// update figure references to their numbered labels
+    pub fn update_figure_references(&mut self, figure_index: &HashMap<String, String>) {
+        let re = Regex::new(FIGURE_REFERENCE).unwrap();
+
+        let new_contents = re
+            .replace_all(&self.contents, |caps: &regex::Captures| {
+                let anchor = format!("{}:{}", &caps[1], &caps[2]);
+                if let Some(label) = figure_index.get(&anchor) {
+                    format!("[{label}](#{anchor})")
+                } else {
+                    caps[0].to_string()
+                }
+            })
+            .to_string();
+
+        self.contents = new_contents;
+    }
+
+    /// citation keys written in the page, including unknown ones
+    pub fn citation_keys(&self) -> Vec<&str> {
+        citations(&self.contents)
+            .into_iter()
+            .flat_map(|citation| citation.keys)
+            .filter(|key| {
+                !RESERVED_ANCHOR_PREFIXES
+                    .iter()
+                    .any(|prefix| key.starts_with(prefix))
+            })
+            .unique()
+            .collect()
+    }
+
+    /// enumerate references in the page
+    pub fn enumerate_references<'a>(&self, bib_entries: &'a [BibEntry]) -> Vec<&'a BibEntry> {
+        // collect keys from the page
+        let keys: Vec<&str> = citations(&self.contents)
+            .into_iter()
+            .flat_map(|citation| citation.keys)
+            .collect();
+
+        // find corresponding BibEntry for each key
+        keys.iter()
+            .filter_map(|&key| bib_entries.iter().find(|entry| entry.tag == key))
+            .unique()
+            .collect()
+    }
+
+    /// update reference item
+    ///
+    /// A multi-citation `[](#a,b,c)` becomes `[1](#a:2 "cite:a,b,c")[](#b:2)–[3](#c:2)`.
+    /// Unknown keys of a multi-citation are kept as `[](#key)` after the numbered links.
+    pub fn update_references(
+        &mut self,
+        page_id: usize,
+        bib_index: &HashMap<&BibEntry, usize>,
+        conf: &BibliographyConf,
+    ) {
+        let mut new_contents = String::new();
+        let mut last = 0;
+
+        for citation in citations(&self.contents) {
+            let entries = citation
+                .keys
+                .iter()
+                .filter_map(|&key| {
+                    bib_index
+                        .iter()
+                        .find(|(entry, _)| entry.tag == key)
+                        .map(|(&entry, &idx)| (entry, idx))
+                })
+                .collect::<Vec<_>>();
+
+            // keep citations without known keys as they are
+            if entries.is_empty() {
+                continue;
+            }
+
+            new_contents += &self.contents[last..citation.range.start];
+            new_contents += &citation_links(&citation.keys, &entries, page_id, conf);
+            last = citation.range.end;
+        }
+
+        new_contents += &self.contents[last..];
+        self.contents = new_contents;
+    }
+
+    /// whether the page has a `<!-- references -->` marker
+    pub fn has_references_marker(&self) -> bool {
+        Regex::new(REFERENCES_MARKER)
+            .unwrap()
+            .is_match(&self.contents)
+    }
+
+    /// replace the generated region after the `<!-- references -->` marker
+    fn update_references_list(&mut self, items: &[String]) {
+        let region = if items.is_empty() {
+            "<!-- references -->\n<!-- /references -->".to_string()
+        } else {
+            format!(
+                "<!-- references -->\n<div class=\"references\">\n\n{}\n\n</div>\n<!-- /references -->",
+                items.join("\n")
+            )
+        };
+
+        let re = Regex::new(REFERENCES_MARKER).unwrap();
+        self.contents = re
+            .replacen(&self.contents, 1, regex::NoExpand(&region))
+            .to_string();
+    }
+
     /// update reference of footnote
     pub fn update_bib_footnote(
         &mut self,
         page_id: usize,
         references: &[&BibEntry],
         bib_index: &HashMap<&BibEntry, usize>,
+        conf: &BibliographyConf,
     ) {
         if references.is_empty() {
             return;
         }
 
-        let new_bibliography = Self::generate_new_bibliography(page_id, references, bib_index);
+        let new_bibliography =
+            Self::generate_new_bibliography(page_id, references, bib_index, conf);
 
         let re = Regex::new(r#"(?s)<div class="footnote">(.*?)</div>"#).unwrap();
 
@@ -181,10 +740,20 @@ impl SlidePage {
         {
             let old_footnote = &self.contents[fstart..fend];
 
-            // clear references
+            // clear references, keeping other lines written by hand
             let re = Regex::new(r#"(?m)<span id=".*">.*</span>.*"#).unwrap();
-            let mut new_footnote = re.replace_all(old_footnote, "").to_string();
+            let kept = re
+                .replace_all(old_footnote, "")
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .join("\n");
 
+            // same layout as a new footnote, so that running again makes no diff
+            let mut new_footnote = "\n\n".to_string();
+            if !kept.is_empty() {
+                new_footnote += &kept;
+                new_footnote += "\n";
+            }
             new_footnote += &new_bibliography;
 
             // replace old footnote
@@ -197,9 +766,7 @@ impl SlidePage {
 
 <div class="footnote">
 
-{new_bibliography}
-
-</div>
+{new_bibliography}</div>
 "#
             );
         }
@@ -209,25 +776,217 @@ impl SlidePage {
         page_id: usize,
         references: &[&BibEntry],
         bib_index: &HashMap<&BibEntry, usize>,
+        conf: &BibliographyConf,
     ) -> String {
         references
             .iter()
             .map(|e| (bib_index[e], e))
             .sorted_by_key(|(k, _)| *k)
-            .map(|(k, e)| {
-                format!(
-                    r#"<span id="{}:{}">[{}]</span> {}"#,
-                    e.tag,
-                    page_id,
-                    k,
-                    e.format()
-                )
-            })
+            .map(|(k, e)| citation_footnote(conf, e, k, &format!("{}:{page_id}", e.tag)))
             .join("\n")
             + "\n\n"
     }
 }
 
+/// caption label for the kind of figure annotation
+fn figure_kind_label(kind: &str) -> &'static str {
+    match kind {
+        "tab" => "Table",
+        _ => "Fig.",
+    }
+}
+
+/// a citation of one or more keys in the text
+struct Citation<'a> {
+    /// byte range of the citation
+    range: Range<usize>,
+    /// cited keys in the written order
+    keys: Vec<&'a str>,
+}
+
+/// citations in the text
+///
+/// Links with other text than a number are citations only when they carry
+/// the page suffix written by `bib`, so `[see here](#intro)` is left alone.
+fn citations(contents: &str) -> Vec<Citation<'_>> {
+    let single = Regex::new(CITATION).unwrap();
+    let group = Regex::new(CITATION_GROUP).unwrap();
+
+    // processed multi-citations with the links following the first one
+    let mut found = vec![];
+    for caps in group.captures_iter(contents) {
+        let keys = split_keys(caps.get(4).unwrap().as_str());
+        let mut end = caps.get(0).unwrap().end();
+
+        while let Some(next) = CITATION_GROUP_SEPARATORS.iter().find_map(|separator| {
+            let start = end + separator.len();
+            contents[end..].starts_with(separator).then_some(())?;
+            let caps = single.captures_at(contents, start)?;
+            let link = caps.get(0).unwrap();
+            (link.start() == start && keys.contains(&&caps[2])).then_some(link.end())
+        }) {
+            end = next;
+        }
+
+        found.push(Citation {
+            range: caps.get(0).unwrap().start()..end,
+            keys,
+        });
+    }
+
+    let singles = single
+        .captures_iter(contents)
+        .filter(is_citation)
+        .filter(|caps| {
+            let start = caps.get(0).unwrap().start();
+            !found.iter().any(|citation| citation.range.contains(&start))
+        })
+        .map(|caps| Citation {
+            range: caps.get(0).unwrap().range(),
+            keys: split_keys(caps.get(2).unwrap().as_str()),
+        })
+        .collect::<Vec<_>>();
+
+    found.extend(singles);
+    found.sort_by_key(|citation| citation.range.start);
+    found
+}
+
+fn split_keys(keys: &str) -> Vec<&str> {
+    keys.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+fn is_citation(caps: &regex::Captures) -> bool {
+    caps[1].chars().all(|c| c.is_ascii_digit()) || caps.get(3).is_some()
+}
+
+/// links of a citation; numbered markers of a multi-citation are sorted and
+/// consecutive runs of three or more are compressed into a range
+///
+/// The entries inside a range keep empty links to their footnotes, and keys without an
+/// entry are left as `[](#key)`.
+fn citation_links(
+    keys: &[&str],
+    entries: &[(&BibEntry, usize)],
+    page_id: usize,
+    conf: &BibliographyConf,
+) -> String {
+    let numeric = matches!(conf.style, CitationStyle::Plain | CitationStyle::Ieee);
+    let separator = if numeric { ", " } else { "; " };
+
+    let links = known_citation_links(keys, entries, page_id, conf);
+    keys.iter()
+        .filter(|&&key| !entries.iter().any(|(entry, _)| entry.tag == key))
+        .fold(links, |links, key| format!("{links}{separator}[](#{key})"))
+}
+
+fn known_citation_links(
+    keys: &[&str],
+    entries: &[(&BibEntry, usize)],
+    page_id: usize,
+    conf: &BibliographyConf,
+) -> String {
+    let link = |(entry, idx): (&BibEntry, usize), title: &str| {
+        let marker = citation_marker(conf, entry, idx);
+        format!("[{marker}](#{}:{page_id}{title})", entry.tag)
+    };
+
+    if let [entry] = entries {
+        return link(*entry, "");
+    }
+
+    let title = format!(" \"cite:{}\"", keys.join(","));
+
+    if !matches!(conf.style, CitationStyle::Plain | CitationStyle::Ieee) {
+        return entries
+            .iter()
+            .enumerate()
+            .map(|(i, &entry)| link(entry, if i == 0 { &title } else { "" }))
+            .join("; ");
+    }
+
+    let sorted = entries
+        .iter()
+        .copied()
+        .sorted_by_key(|(_, idx)| *idx)
+        .dedup_by(|(_, a), (_, b)| a == b)
+        .collect::<Vec<_>>();
+
+    // runs of consecutive numbers
+    let mut runs: Vec<Vec<(&BibEntry, usize)>> = vec![];
+    for entry in sorted {
+        match runs.last_mut() {
+            Some(run) if run.last().unwrap().1 + 1 == entry.1 => run.push(entry),
+            _ => runs.push(vec![entry]),
+        }
+    }
+
+    let mut first = true;
+    let mut link = |entry| {
+        let title = if first { title.as_str() } else { "" };
+        first = false;
+        link(entry, title)
+    };
+
+    runs.into_iter()
+        .map(|run| match run.as_slice() {
+            [start, inner @ .., end] if run.len() >= 3 => {
+                let start = link(*start);
+                let inner = inner
+                    .iter()
+                    .map(|&(entry, _)| format!("[](#{}:{page_id})", entry.tag))
+                    .join("");
+                format!("{start}{inner}–{}", link(*end))
+            }
+            _ => run.into_iter().map(&mut link).join(", "),
+        })
+        .join(", ")
+}
+
+/// fenced code blocks followed line by line
+///
+/// A block is closed only by a fence of the same character, at least as long as the
+/// opening one, so longer fences can wrap code containing ```.
+#[derive(Debug, Default)]
+pub(crate) struct Fences {
+    /// character and length of the open fence
+    open: Option<(char, usize)>,
+}
+
+impl Fences {
+    /// whether the line opens or closes a fenced code block, updating the state
+    pub fn toggles(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let Some(c) = line.chars().next().filter(|c| matches!(c, '`' | '~')) else {
+            return false;
+        };
+        let len = line.chars().take_while(|&x| x == c).count();
+        if len < 3 {
+            return false;
+        }
+
+        match self.open {
+            None => {
+                self.open = Some((c, len));
+                true
+            }
+            Some((open, open_len)) if open == c && len >= open_len && len == line.len() => {
+                self.open = None;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// whether the last line is inside a fenced code block
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+}
+
 #[cfg(test)]
 mod test_contents {
     use super::*;
@@ -283,6 +1042,384 @@ Some content here.
 
         let result = SlideContents::try_from(s);
         assert!(result.is_err());
+
+        let slide_contents = SlideContents::parse_without_frontmatter_check(s).unwrap();
+        assert_eq!(slide_contents.frontmatter, "");
+        assert_eq!(slide_contents.pages.len(), 1);
+        assert_eq!(slide_contents.to_marp(), s);
+    }
+
+    #[test]
+    fn test_slide_contents_in_long_fences() {
+        let s = "---\nmarp: true\n---\n# Usage\n\n````markdown\n```bash\nrun\n```\n\n---\n````\n\n---\n\n# Next\n";
+
+        let slide_contents = SlideContents::try_from(s).unwrap();
+
+        assert_eq!(slide_contents.pages.len(), 2);
+        assert_eq!(slide_contents.pages[0].code_block_count(), 1);
+        assert_eq!(
+            slide_contents.pages[0].markdown_lines(),
+            vec![(0, "# Usage"), (1, "")]
+        );
+    }
+
+    #[test]
+    fn test_slide_contents_keeps_layout() {
+        let sources = [
+            "---\nmarp: true\n---\n\n# Slide 1\n\n---\n\n# Slide 2\n",
+            "---\nmarp: true\n---\n# Slide 1\n----\n# Slide 2",
+            "---\r\nmarp: true\r\n---\r\n\r\n# Slide 1\r\n\r\n---\r\n# Slide 2\r\n",
+            "---\nmarp: true\n---\n# Slide 1\n\n---\n\n---\n",
+        ];
+        for s in sources {
+            assert_eq!(SlideContents::try_from(s).unwrap().to_marp(), s);
+        }
+
+        // added pages are separated with blank lines
+        let mut slide_contents =
+            SlideContents::try_from("---\nmarp: true\n---\n# Slide 1\n").unwrap();
+        slide_contents.pages.push(SlidePage::new("# Slide 2"));
+        assert_eq!(
+            slide_contents.to_marp(),
+            "---\nmarp: true\n---\n# Slide 1\n\n---\n\n# Slide 2\n"
+        );
+    }
+
+    #[test]
+    fn test_modify_cross_references() {
+        let s = r#"---
+marp: true
+---
+# Intro
+See [](#ref:results) and [](#ref:intro).
+
+---
+<!-- label: results -->
+# Results
+
+---
+<!-- label: intro -->
+# Moved
+Back to [3](#3 "ref:results").
+"#;
+
+        let mut slide_contents = SlideContents::try_from(s).unwrap();
+        slide_contents.modify_cross_references().unwrap();
+
+        assert_eq!(
+            slide_contents.pages[0].contents,
+            r#"# Intro
+See [2](#2 "ref:results") and [3](#3 "ref:intro")."#
+        );
+        assert_eq!(
+            slide_contents.pages[2].contents,
+            r#"<!-- label: intro -->
+# Moved
+Back to [2](#2 "ref:results")."#
+        );
+
+        // idempotent
+        let marp = slide_contents.to_marp();
+        let mut again = SlideContents::try_from(marp.as_str()).unwrap();
+        again.modify_cross_references().unwrap();
+        assert_eq!(again.to_marp(), marp);
+    }
+
+    #[test]
+    fn test_modify_cross_references_rejects_unknown_label() {
+        let s = r#"---
+marp: true
+---
+# Intro
+See [](#ref:missing).
+"#;
+
+        let mut slide_contents = SlideContents::try_from(s).unwrap();
+        let err = slide_contents.modify_cross_references().unwrap_err();
+
+        assert_eq!(err.to_string(), "Unresolved labels: missing");
+    }
+
+    #[test]
+    fn test_modify_figures() {
+        let s = r#"---
+marp: true
+---
+# Overview
+As shown in [](#fig:arch) and [](#tab:result).
+
+![architecture](images/arch.png)
+<!-- fig: arch "System architecture" -->
+
+---
+# Results
+<!-- tab: result "Accuracy" -->
+| model | acc |
+| ----- | --- |
+
+![detail](images/detail.png)
+<!-- fig: detail -->
+"#;
+
+        let mut slide_contents = SlideContents::try_from(s).unwrap();
+        slide_contents.modify_figures().unwrap();
+
+        assert_eq!(
+            slide_contents.pages[0].contents,
+            r#"# Overview
+As shown in [Fig. 1](#fig:arch) and [Table 1](#tab:result).
+
+![architecture](images/arch.png)
+<!-- fig: arch "System architecture" -->
+<div class="caption" id="fig:arch">Fig. 1: System architecture</div>"#
+        );
+        assert!(slide_contents.pages[1]
+            .contents
+            .contains(r#"<div class="caption" id="fig:detail">Fig. 2</div>"#));
+
+        // reorder figures and run again
+        slide_contents.pages.swap(0, 1);
+        let marp = slide_contents.to_marp();
+        let mut again = SlideContents::try_from(marp.as_str()).unwrap();
+        again.modify_figures().unwrap();
+
+        assert!(again.pages[0]
+            .contents
+            .contains(r#"<div class="caption" id="fig:detail">Fig. 1</div>"#));
+        assert!(again.pages[1]
+            .contents
+            .contains(r#"As shown in [Fig. 2](#fig:arch) and [Table 1](#tab:result)."#));
+        assert_eq!(
+            again.pages[1].contents.matches("class=\"caption\"").count(),
+            1
+        );
+
+        let marp = again.to_marp();
+        let mut third = SlideContents::try_from(marp.as_str()).unwrap();
+        third.modify_figures().unwrap();
+        assert_eq!(third.to_marp(), marp);
+    }
+
+    #[test]
+    fn test_modify_bibliography_with_author_year_style() {
+        let bib = vec![BibEntry {
+            tag: "knuth84".to_string(),
+            authors: Some("Donald E. Knuth".to_string()),
+            title: "Literate Programming".to_string(),
+            year: Some(1984),
+            venue: None,
+            url: None,
+            ..Default::default()
+        }];
+        let conf = BibliographyConf {
+            style: CitationStyle::AuthorYear,
+            ..Default::default()
+        };
+
+        let s = r#"---
+marp: true
+---
+# Slide 1
+As argued by ([](#knuth84)), see [the intro](#intro).
+"#;
+
+        let mut slide_contents = SlideContents::try_from(s).unwrap();
+        slide_contents.modify_bibliography_with_style(&bib, &conf);
+
+        assert!(slide_contents.pages[0]
+            .contents
+            .contains("As argued by ([Knuth 1984](#knuth84:1)), see [the intro](#intro)."));
+        assert!(slide_contents.pages[0].contents.contains(
+            r#"<span id="knuth84:1">Donald E. Knuth. 1984. Literate Programming.</span>"#
+        ));
+
+        // running again keeps the markers and footnotes
+        let marp = slide_contents.to_marp();
+        let mut again = SlideContents::try_from(marp.as_str()).unwrap();
+        again.modify_bibliography_with_style(&bib, &conf);
+        assert_eq!(again.to_marp(), marp);
+        assert_eq!(again.pages[0].citation_keys(), vec!["knuth84"]);
+    }
+
+    #[test]
+    fn test_modify_bibliography_with_multi_citations() {
+        let bib = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(|tag| BibEntry {
+                tag: tag.to_string(),
+                authors: Some(format!("Author {}", tag.to_uppercase())),
+                title: format!("Title {tag}"),
+                year: Some(2020),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let s = r#"---
+marp: true
+---
+# Slide 1
+Single [](#d), range [](#c,a,b), pair [](#a,d), unknown [](#a,x).
+"#;
+
+        let mut slide_contents = SlideContents::try_from(s).unwrap();
+        slide_contents.modify_bibliography(&bib);
+
+        assert!(slide_contents.pages[0].contents.starts_with(
+            r#"# Slide 1
+Single [1](#d:1), range [2](#c:1 "cite:c,a,b")[](#a:1)–[4](#b:1), pair [1](#d:1 "cite:a,d"), [3](#a:1), unknown [3](#a:1), [](#x)."#
+        ));
+        let text = slide_contents.pages[0].contents.lines().nth(1).unwrap();
+        for tag in ["a", "b", "c", "d"] {
+            assert!(slide_contents.pages[0]
+                .contents
+                .contains(&format!(r#"<span id="{tag}:1">"#)));
+            assert!(text.contains(&format!("](#{tag}:1")), "{tag} is not linked");
+        }
+        assert_eq!(
+            slide_contents.pages[0].citation_keys(),
+            vec!["d", "c", "a", "b", "x"]
+        );
+
+        // running again keeps the markers
+        let marp = slide_contents.to_marp();
+        let mut again = SlideContents::try_from(marp.as_str()).unwrap();
+        again.modify_bibliography(&bib);
+        assert_eq!(
+            again.pages[0].contents.lines().nth(1),
+            slide_contents.pages[0].contents.lines().nth(1)
+        );
+
+        // author-year markers are listed in the written order
+        let conf = BibliographyConf {
+            style: CitationStyle::AuthorYear,
+            ..Default::default()
+        };
+        again.modify_bibliography_with_style(&bib, &conf);
+        assert!(again.pages[0]
+            .contents
+            .contains(r#"pair [A 2020](#a:1 "cite:a,d"); [D 2020](#d:1), unknown"#));
+        assert!(again.pages[0]
+            .contents
+            .contains("unknown [A 2020](#a:1), [](#x)."));
+
+        // every entry inside a range links to its footnote
+        let mut slide_contents =
+            SlideContents::try_from("---\nmarp: true\n---\n# Slide 1\nAll [](#a,b,c,d).\n")
+                .unwrap();
+        slide_contents.modify_bibliography(&bib);
+        assert!(slide_contents.pages[0]
+            .contents
+            .contains(r#"All [1](#a:1 "cite:a,b,c,d")[](#b:1)[](#c:1)–[4](#d:1)."#));
+        let marp = slide_contents.to_marp();
+        let mut again = SlideContents::try_from(marp.as_str()).unwrap();
+        again.modify_bibliography(&bib);
+        assert_eq!(again.to_marp(), marp);
+    }
+
+    #[test]
+    fn test_modify_bibliography_fills_references_pages() {
+        let bib = ["a", "b", "c"]
+            .into_iter()
+            .map(|tag| BibEntry {
+                tag: tag.to_string(),
+                title: format!("Title {tag}"),
+                year: Some(2020),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let conf = BibliographyConf {
+            references_per_page: Some(2),
+            ..Default::default()
+        };
+
+        let s = r#"---
+marp: true
+---
+# Intro
+See [](#b) and [](#a).
+
+---
+
+# More
+Again [](#a), then [](#c).
+
+---
+
+# References
+
+<!-- references -->
+"#;
+
+        let mut slide_contents = SlideContents::try_from(s).unwrap();
+        slide_contents.modify_bibliography_with_style(&bib, &conf);
+
+        assert_eq!(slide_contents.pages.len(), 4);
+        assert_eq!(
+            slide_contents.pages[2].contents,
+            r#"# References
+
+<!-- references -->
+<div class="references">
+
+<span id="b">[1]</span> Title b. 2020 (cited on [p. 1](#1))
+<span id="a">[2]</span> Title a. 2020 (cited on [p. 1](#1), [p. 2](#2))
+
+</div>
+<!-- /references -->"#
+        );
+        assert!(slide_contents.pages[3].contents.starts_with(
+            "# References (cont.)\n\n<!-- references: continued -->\n<!-- references -->"
+        ));
+        assert!(slide_contents.pages[3]
+            .contents
+            .contains(r#"<span id="c">[3]</span> Title c. 2020 (cited on [p. 2](#2))"#));
+        assert!(slide_contents.pages[2].citation_keys().is_empty());
+
+        // running again regenerates the same pages
+        let marp = slide_contents.to_marp();
+        let mut again = SlideContents::try_from(marp.as_str()).unwrap();
+        again.modify_bibliography_with_style(&bib, &conf);
+        assert_eq!(again.pages.len(), 4);
+        assert_eq!(again.pages[2].contents, slide_contents.pages[2].contents);
+        assert_eq!(again.pages[3].contents, slide_contents.pages[3].contents);
+
+        // pages inserted before the citations move the back-links
+        again.pages.insert(0, SlidePage::new("# Agenda"));
+        again.update_citing_pages(&bib, &conf);
+        assert_eq!(again.pages.len(), 5);
+        assert!(again.pages[3].contents.contains(
+            r#"<span id="a">[2]</span> Title a. 2020 (cited on [p. 2](#2), [p. 3](#3))"#
+        ));
+        assert!(again.pages[4]
+            .contents
+            .contains(r#"<span id="c">[3]</span> Title c. 2020 (cited on [p. 3](#3))"#));
+    }
+
+    #[test]
+    fn test_modify_bibliography_appends_references_page() {
+        let bib = vec![BibEntry {
+            tag: "a".to_string(),
+            title: "Title a".to_string(),
+            ..Default::default()
+        }];
+        let conf = BibliographyConf {
+            references_page: true,
+            ..Default::default()
+        };
+
+        let mut slide_contents =
+            SlideContents::try_from("---\nmarp: true\n---\n# Intro\nSee [](#a).\n").unwrap();
+        slide_contents.modify_bibliography_with_style(&bib, &conf);
+
+        assert_eq!(slide_contents.pages.len(), 2);
+        assert!(slide_contents.pages[1]
+            .contents
+            .starts_with("# References\n\n<!-- references -->\n"));
+
+        let marp = slide_contents.to_marp();
+        let mut again = SlideContents::try_from(marp.as_str()).unwrap();
+        again.modify_bibliography_with_style(&bib, &conf);
+        assert_eq!(again.pages.len(), 2);
     }
 
     #[test]
@@ -292,17 +1429,19 @@ Some content here.
                 tag: "ref1".to_string(),
                 authors: Some("Author A".to_string()),
                 title: "Title A".to_string(),
-                year: 2020,
+                year: Some(2020),
                 venue: Some("Venue A".to_string()),
                 url: Some("https://doi.org/xxxx".to_string()),
+                ..Default::default()
             },
             BibEntry {
                 tag: "ref2".to_string(),
                 authors: Some("Author B".to_string()),
                 title: "Title B".to_string(),
-                year: 2021,
+                year: Some(2021),
                 venue: Some("Venue B".to_string()),
                 url: Some("https://doi.org/yyyy".to_string()),
+                ..Default::default()
             },
         ];
 
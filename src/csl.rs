@@ -0,0 +1,197 @@
+//! read CSL-JSON returned by DOI resolvers
+
+use anyhow::bail;
+use serde_json::Value;
+
+use crate::config::BibEntry;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// convert a CSL-JSON item into a bibliography entry
+///
+/// The tag is generated from the first author, the year and the first title
+/// word (e.g. `knuth1984literate`) unless given.
+pub fn csl_to_bib_entry(csl: &Value, tag: Option<&str>) -> anyhow::Result<BibEntry> {
+    let Some(title) = text(&csl["title"]) else {
+        bail!("CSL-JSON item has no title");
+    };
+
+    let authors = csl["author"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(author_name)
+        .collect::<Vec<_>>();
+
+    let date = csl["issued"]["date-parts"][0]
+        .as_array()
+        .or_else(|| csl["published-print"]["date-parts"][0].as_array())
+        .or_else(|| csl["published-online"]["date-parts"][0].as_array());
+    let year = date
+        .and_then(|parts| number(parts.first()?))
+        .and_then(|year| u16::try_from(year).ok());
+    let month = date
+        .and_then(|parts| number(parts.get(1)?))
+        .and_then(|month| MONTHS.get(usize::try_from(month).ok()?.checked_sub(1)?))
+        .map(|month| month.to_string());
+
+    let tag = match tag {
+        Some(tag) => tag.to_string(),
+        None => generate_tag(&csl["author"], year, &title),
+    };
+
+    Ok(BibEntry {
+        tag,
+        kind: csl["type"]
+            .as_str()
+            .map(|kind| bibtex_kind(kind).to_string()),
+        title,
+        authors: (!authors.is_empty()).then(|| authors.join(", ")),
+        year,
+        month,
+        venue: text(&csl["container-title"]),
+        volume: text(&csl["volume"]),
+        pages: text(&csl["page"]).map(|pages| pages.replace('-', "–")),
+        publisher: text(&csl["publisher"]),
+        doi: text(&csl["DOI"]),
+        arxiv: None,
+        note: None,
+        url: text(&csl["URL"]).filter(|url| !url.starts_with("https://doi.org/")),
+    })
+}
+
+/// string, number, or the first string of an array
+fn text(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(values) => return values.iter().find_map(text),
+        _ => return None,
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// number, or a string of a number
+fn number(value: &Value) -> Option<i64> {
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// `Given Family`, or a literal name
+fn author_name(author: &Value) -> Option<String> {
+    if let Some(literal) = text(&author["literal"]) {
+        return Some(literal);
+    }
+    match (text(&author["given"]), text(&author["family"])) {
+        (Some(given), Some(family)) => Some(format!("{given} {family}")),
+        (None, Some(family)) => Some(family),
+        (Some(given), None) => Some(given),
+        (None, None) => None,
+    }
+}
+
+/// BibTeX entry type of a CSL item type
+fn bibtex_kind(csl_type: &str) -> &str {
+    match csl_type {
+        "article-journal" | "article-magazine" | "article-newspaper" | "journal-article" => {
+            "article"
+        }
+        "paper-conference" | "proceedings-article" => "inproceedings",
+        "book" | "monograph" => "book",
+        "chapter" | "book-chapter" => "incollection",
+        "thesis" | "dissertation" => "phdthesis",
+        "report" => "techreport",
+        _ => "misc",
+    }
+}
+
+fn generate_tag(authors: &Value, year: Option<u16>, title: &str) -> String {
+    let keyword = |s: &str| {
+        s.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase()
+    };
+
+    let first_author = authors
+        .get(0)
+        .and_then(|author| text(&author["family"]).or_else(|| text(&author["literal"])))
+        .and_then(|name| name.split_whitespace().last().map(keyword))
+        .unwrap_or_default();
+    let title_word = title
+        .split_whitespace()
+        .map(keyword)
+        .find(|word| word.len() > 3)
+        .unwrap_or_default();
+
+    format!(
+        "{first_author}{}{title_word}",
+        year.map(|year| year.to_string()).unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::config::BibEntry;
+
+    use super::csl_to_bib_entry;
+
+    #[test]
+    fn test_csl_to_bib_entry() {
+        let csl = json!({
+            "type": "article-journal",
+            "title": "Literate Programming",
+            "author": [{ "given": "D. E.", "family": "Knuth" }],
+            "issued": { "date-parts": [[1984, 2, 1]] },
+            "container-title": ["The Computer Journal"],
+            "volume": 27,
+            "page": "97-111",
+            "publisher": "Oxford University Press (OUP)",
+            "DOI": "10.1093/comjnl/27.2.97",
+            "URL": "https://doi.org/10.1093/comjnl/27.2.97"
+        });
+
+        assert_eq!(
+            csl_to_bib_entry(&csl, None).unwrap(),
+            BibEntry {
+                tag: "knuth1984literate".to_string(),
+                kind: Some("article".to_string()),
+                title: "Literate Programming".to_string(),
+                authors: Some("D. E. Knuth".to_string()),
+                year: Some(1984),
+                month: Some("February".to_string()),
+                venue: Some("The Computer Journal".to_string()),
+                volume: Some("27".to_string()),
+                pages: Some("97–111".to_string()),
+                publisher: Some("Oxford University Press (OUP)".to_string()),
+                doi: Some("10.1093/comjnl/27.2.97".to_string()),
+                ..Default::default()
+            }
+        );
+
+        let undated = json!({ "title": "Notes", "author": [{ "literal": "W3C" }] });
+        let entry = csl_to_bib_entry(&undated, Some("w3c-notes")).unwrap();
+        assert_eq!(entry.tag, "w3c-notes");
+        assert_eq!(entry.year, None);
+        assert_eq!(entry.authors.as_deref(), Some("W3C"));
+
+        assert!(csl_to_bib_entry(&json!({}), None).is_err());
+    }
+}
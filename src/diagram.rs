@@ -0,0 +1,355 @@
+//! render Mermaid / Graphviz code blocks into the image cache
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, Context};
+
+use crate::{
+    contents::Fences,
+    images::{map_image_urls, relative_path, scan_image_usages},
+    project::Project,
+    slide::Slide,
+};
+
+/// diagram languages rendered at build time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum DiagramKind {
+    Mermaid,
+    Dot,
+}
+
+impl DiagramKind {
+    fn from_info(info: &str) -> Option<Self> {
+        match info.split_whitespace().next()? {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Dot),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Mermaid => "mmd",
+            Self::Dot => "dot",
+        }
+    }
+}
+
+/// a fenced diagram block
+#[derive(Debug, Clone)]
+struct DiagramBlock {
+    kind: DiagramKind,
+    /// first and last line (inclusive) of the block
+    lines: (usize, usize),
+    source: String,
+}
+
+/// replace diagram code blocks with `<img>` tags pointing to rendered SVGs
+///
+/// Blocks are left as they are when the renderer is disabled or missing.
+pub fn render_diagrams(project: &Project, slide: &Slide, contents: &str) -> anyhow::Result<String> {
+    let blocks = scan_diagram_blocks(contents)
+        .into_iter()
+        .filter(|block| renderer(project, block.kind).is_some())
+        .collect::<Vec<_>>();
+
+    if blocks.is_empty() {
+        return Ok(contents.to_string());
+    }
+
+    let cache_dir = project.root_dir.join(&project.conf.images.cache_dir);
+    fs::create_dir_all(&cache_dir)?;
+
+    let lines = contents.lines().collect::<Vec<_>>();
+    let mut rendered = vec![];
+    let mut next_line = 0;
+
+    for block in blocks {
+        let tool = renderer(project, block.kind).unwrap();
+        let output = cache_dir.join(format!("diagram-{}.svg", cache_key(&block, tool)));
+
+        rendered.extend(
+            lines[next_line..block.lines.0]
+                .iter()
+                .map(|l| l.to_string()),
+        );
+        next_line = block.lines.0;
+
+        if !output.exists() && !render_one(project, tool, &block, &output)? {
+            continue;
+        }
+
+        let src = relative_path(&absolute(&slide.dir)?, &absolute(&output)?);
+        rendered.push(format!(
+            r#"<img src="{}" alt="{} diagram">"#,
+            src.to_string_lossy(),
+            match block.kind {
+                DiagramKind::Mermaid => "mermaid",
+                DiagramKind::Dot => "graphviz",
+            }
+        ));
+        next_line = block.lines.1 + 1;
+    }
+
+    rendered.extend(lines[next_line..].iter().map(|l| l.to_string()));
+
+    let mut rendered = rendered.join("\n");
+    if contents.ends_with('\n') {
+        rendered.push('\n');
+    }
+
+    Ok(rendered)
+}
+
+/// rendered diagram SVGs referenced by contents returned from [`render_diagrams`]
+pub fn diagram_images(
+    project: &Project,
+    slide: &Slide,
+    contents: &str,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut images = vec![];
+    for usage in scan_image_usages(contents) {
+        if usage.is_local() {
+            if let Some(path) = diagram_image_path(project, &usage.local_path(&slide.dir))? {
+                if !images.contains(&path) {
+                    images.push(path);
+                }
+            }
+        }
+    }
+
+    Ok(images)
+}
+
+/// point rendered diagrams to `images_dir` instead of the image cache
+///
+/// Used for HTML output, where the SVGs are copied next to `index.html`.
+pub fn link_diagram_images(
+    project: &Project,
+    slide: &Slide,
+    contents: &str,
+    images_dir: &Path,
+) -> anyhow::Result<String> {
+    let images = diagram_images(project, slide, contents)?;
+    if images.is_empty() {
+        return Ok(contents.to_string());
+    }
+
+    Ok(map_image_urls(contents, |url| {
+        let path = absolute(&slide.dir.join(url)).ok()?;
+        if !images.contains(&path) {
+            return None;
+        }
+        Some(
+            images_dir
+                .join(path.file_name()?)
+                .to_string_lossy()
+                .to_string(),
+        )
+    }))
+}
+
+/// canonical path of `path` if it is a rendered diagram in the image cache
+fn diagram_image_path(project: &Project, path: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let cache_dir = project.root_dir.join(&project.conf.images.cache_dir);
+    if !path.is_file() || !cache_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let path = absolute(path)?;
+    let is_diagram = path.parent() == Some(absolute(&cache_dir)?.as_path())
+        && path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("diagram-"));
+
+    Ok(is_diagram.then_some(path))
+}
+
+fn scan_diagram_blocks(contents: &str) -> Vec<DiagramBlock> {
+    let mut blocks = vec![];
+    let mut fences = Fences::default();
+    let mut current: Option<(Option<DiagramKind>, usize, Vec<&str>)> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        if !fences.toggles(line) {
+            if let Some((_, _, body)) = &mut current {
+                body.push(line);
+            }
+            continue;
+        }
+
+        match current.take() {
+            None => {
+                let info = line.trim().trim_start_matches(['`', '~']);
+                current = Some((DiagramKind::from_info(info), index, vec![]));
+            }
+            Some((kind, start, body)) => {
+                if let Some(kind) = kind {
+                    blocks.push(DiagramBlock {
+                        kind,
+                        lines: (start, index),
+                        source: body.join("\n") + "\n",
+                    });
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+/// renderer command of the diagram kind if enabled
+fn renderer(project: &Project, kind: DiagramKind) -> Option<&str> {
+    let conf = &project.conf.images;
+    match kind {
+        DiagramKind::Mermaid if conf.mermaid.enabled => Some(&conf.mermaid.tool),
+        DiagramKind::Dot if conf.dot.enabled => Some(&conf.dot.tool),
+        _ => None,
+    }
+}
+
+fn cache_key(block: &DiagramBlock, tool: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    block.kind.hash(&mut hasher);
+    block.source.hash(&mut hasher);
+    tool.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// render a diagram; `false` when the renderer is missing
+fn render_one(
+    project: &Project,
+    tool: &str,
+    block: &DiagramBlock,
+    output: &Path,
+) -> anyhow::Result<bool> {
+    let input = output.with_extension(block.kind.extension());
+    fs::write(&input, &block.source)?;
+
+    let mut cmd = Command::new(tool);
+    match block.kind {
+        DiagramKind::Mermaid => cmd.arg("-i").arg(&input).arg("-o").arg(output),
+        DiagramKind::Dot => cmd.arg("-Tsvg").arg(&input).arg("-o").arg(output),
+    };
+
+    let result = cmd.output();
+    fs::remove_file(&input)?;
+
+    let result = match result {
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            if project.conf.images.fail_on_missing_tool {
+                return Err(anyhow!("diagram renderer is missing: {tool}"));
+            }
+            log::warn!("diagram renderer is missing: {tool}; keep the code block");
+            return Ok(false);
+        }
+        result => result.with_context(|| format!("failed to run {tool}"))?,
+    };
+
+    if !result.status.success() {
+        return Err(anyhow!(
+            "diagram renderer failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        ));
+    }
+
+    Ok(true)
+}
+
+fn absolute(path: &Path) -> anyhow::Result<PathBuf> {
+    path.canonicalize()
+        .with_context(|| format!("Path does not exist: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::unix::fs::PermissionsExt, path::PathBuf};
+
+    use crate::{
+        config::{ProjectConf, SlideConf},
+        project::Project,
+        slide::Slide,
+    };
+
+    use super::{render_diagrams, scan_diagram_blocks, DiagramKind};
+
+    #[test]
+    fn test_scan_diagram_blocks() {
+        let blocks = scan_diagram_blocks(
+            "# Flow\n\n```mermaid\ngraph LR\n  A --> B\n```\n\n```rust\nfn main() {}\n```\n\n~~~dot\ndigraph { a -> b }\n~~~\n",
+        );
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].kind, DiagramKind::Mermaid);
+        assert_eq!(blocks[0].lines, (2, 5));
+        assert_eq!(blocks[0].source, "graph LR\n  A --> B\n");
+        assert_eq!(blocks[1].kind, DiagramKind::Dot);
+        assert_eq!(blocks[1].lines, (11, 13));
+    }
+
+    #[test]
+    fn test_scan_diagram_blocks_in_long_fences() {
+        let blocks = scan_diagram_blocks(
+            "````markdown\n```mermaid\ngraph LR\n```\n````\n\n```dot\ndigraph { a -> b }\n```\n",
+        );
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].kind, DiagramKind::Dot);
+        assert_eq!(blocks[0].lines, (6, 8));
+        assert_eq!(blocks[0].source, "digraph { a -> b }\n");
+    }
+
+    #[test]
+    fn test_render_diagrams_replaces_blocks_with_cached_svg() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let slide_dir = root.join("src/intro");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+
+        // fake renderer: `dot -Tsvg <input> -o <output>`
+        let tool = root.join("fake-dot");
+        std::fs::write(&tool, "#!/bin/sh\necho '<svg/>' > \"$4\"\n").unwrap();
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut conf = ProjectConf::default();
+        conf.images.dot.tool = tool.to_string_lossy().to_string();
+        conf.images.dot.enabled = true;
+        let project = Project {
+            root_dir: root.to_path_buf(),
+            conf,
+            slides: vec![],
+        };
+        let slide = Slide {
+            dir: PathBuf::from(&slide_dir),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 1,
+                ..Default::default()
+            },
+        };
+
+        let rendered = render_diagrams(
+            &project,
+            &slide,
+            "# Graph\n\n```dot\ndigraph { a -> b }\n```\n\n```mermaid\ngraph LR\n```\n",
+        )
+        .unwrap();
+
+        let src = regex::Regex::new(r#"<img src="([^"]+)" alt="graphviz diagram">"#)
+            .unwrap()
+            .captures(&rendered)
+            .unwrap()[1]
+            .to_string();
+        assert!(src.starts_with("../../.slide-flow/cache/images/diagram-"));
+        assert!(slide_dir.join(&src).is_file());
+        assert!(rendered.starts_with("# Graph\n\n<img "));
+        assert!(rendered.ends_with("\n\n```mermaid\ngraph LR\n```\n"));
+    }
+}
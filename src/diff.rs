@@ -0,0 +1,50 @@
+//! unified diffs of slide sources
+
+use std::path::Path;
+
+use similar::TextDiff;
+
+/// unified diff from `old` to `new`, or `None` when they are the same
+pub fn unified_diff(path: &Path, old: &str, new: &str) -> Option<String> {
+    // relative to the working directory like `git diff`
+    let path = std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok())
+        .unwrap_or(path)
+        .to_string_lossy();
+
+    labeled_diff(&format!("a/{path}"), &format!("b/{path}"), old, new)
+}
+
+/// unified diff with the given `---` and `+++` labels, or `None` when they are the same
+pub fn labeled_diff(old_label: &str, new_label: &str, old: &str, new: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+
+    let diff = TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_label, new_label)
+        .to_string();
+
+    Some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::unified_diff;
+
+    #[test]
+    fn test_unified_diff() {
+        let path = Path::new("src/intro/slide.md");
+
+        assert_eq!(unified_diff(path, "# A\n", "# A\n"), None);
+        assert_eq!(
+            unified_diff(path, "# A\n\n# B\n", "# A\n\n# B (1/2)\n").unwrap(),
+            "--- a/src/intro/slide.md\n+++ b/src/intro/slide.md\n@@ -1,3 +1,3 @@\n # A\n \n-# B\n+# B (1/2)\n"
+        );
+    }
+}
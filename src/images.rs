@@ -7,7 +7,8 @@ use std::{
 };
 
 use anyhow::{anyhow, bail, Context};
-use regex::{Captures, Regex};
+use itertools::Itertools;
+use regex::Regex;
 
 use crate::{
     config::{ImageOptimizeMode, ImagesConf},
@@ -84,9 +85,18 @@ pub fn optimize_slide_images(
     slide: &Slide,
     options: &OptimizeOptions,
 ) -> anyhow::Result<OptimizeReport> {
-    let markdown_path = slide.dir.join("slide.md");
-    let contents = fs::read_to_string(&markdown_path)?;
-    let refs = collect_image_refs(slide, &contents)?;
+    let contents = slide.get_expanded_source()?;
+    optimize_contents_images(project, slide, &contents, options)
+}
+
+/// optimize local images referenced by already expanded `contents`
+pub fn optimize_contents_images(
+    project: &Project,
+    slide: &Slide,
+    contents: &str,
+    options: &OptimizeOptions,
+) -> anyhow::Result<OptimizeReport> {
+    let refs = collect_image_refs(slide, contents)?;
     optimize_image_refs(project, slide, refs, options)
 }
 
@@ -129,16 +139,113 @@ pub fn clean_image_cache(project: &Project) -> anyhow::Result<PathBuf> {
     Ok(cache_dir)
 }
 
-fn collect_image_refs(slide: &Slide, contents: &str) -> anyhow::Result<Vec<ImageRef>> {
-    let markdown = Regex::new(r#"!\[[^\]]*\]\((?P<url>[^)\s]+)(?:\s+"[^"]*")?\)"#)?;
-    let html = Regex::new(r#"<img\b[^>]*\bsrc=["'](?P<url>[^"']+)["'][^>]*>"#)?;
-    let mut refs = Vec::new();
+/// image reference written in markdown or HTML
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageUsage {
+    /// URL written in the source
+    pub url: String,
+    /// alternative text (`None` for `<img>` without `alt`)
+    pub alt: Option<String>,
+}
+
+impl ImageUsage {
+    /// whether the image points to a file next to the slide
+    pub fn is_local(&self) -> bool {
+        !should_skip_url(&self.url)
+    }
 
-    for caps in markdown.captures_iter(contents) {
-        push_image_ref(slide, &mut refs, &caps)?;
+    /// local path of the image relative to `slide_dir`
+    pub fn local_path(&self, slide_dir: &Path) -> PathBuf {
+        let path_part = self.url.split(['?', '#']).next().unwrap_or(&self.url);
+        slide_dir.join(path_part)
     }
+}
+
+/// Markdown image `![alt](url "title")`
+const MARKDOWN_IMAGE: &str = r#"!\[(?P<alt>[^\]]*)\]\((?P<url>[^)\s]+)(?:\s+"[^"]*")?\)"#;
+
+/// HTML image `<img src="url">`
+const HTML_IMAGE: &str = r#"<img\b[^>]*\bsrc=["'](?P<url>[^"']+)["'][^>]*>"#;
+
+/// scan image references in the order they appear
+pub fn scan_image_usages(contents: &str) -> Vec<ImageUsage> {
+    let markdown = Regex::new(MARKDOWN_IMAGE).unwrap();
+    let html = Regex::new(HTML_IMAGE).unwrap();
+    let html_alt = Regex::new(r#"\balt=["'](?P<alt>[^"']*)["']"#).unwrap();
+
+    let mut usages = markdown
+        .captures_iter(contents)
+        .map(|caps| (caps.get(0).map_or(0, |m| m.start()), caps))
+        .map(|(start, caps)| {
+            (
+                start,
+                ImageUsage {
+                    url: caps["url"].to_string(),
+                    alt: Some(caps["alt"].to_string()),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
     for caps in html.captures_iter(contents) {
-        push_image_ref(slide, &mut refs, &caps)?;
+        let tag = caps.get(0).unwrap();
+        usages.push((
+            tag.start(),
+            ImageUsage {
+                url: caps["url"].to_string(),
+                alt: html_alt
+                    .captures(tag.as_str())
+                    .map(|alt| alt["alt"].to_string()),
+            },
+        ));
+    }
+
+    usages.sort_by_key(|(start, _)| *start);
+    usages.into_iter().map(|(_, usage)| usage).collect()
+}
+
+/// replace the URL of each image reference for which `f` returns a new one
+///
+/// Only the link targets are rewritten; the rest of the contents is kept as it is.
+pub fn map_image_urls(contents: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let mut rewritten = contents.to_string();
+
+    for pattern in [MARKDOWN_IMAGE, HTML_IMAGE] {
+        let re = Regex::new(pattern).unwrap();
+        let mut output = String::new();
+        let mut last = 0;
+
+        for caps in re.captures_iter(&rewritten) {
+            let url = caps.name("url").unwrap();
+            if let Some(new_url) = f(url.as_str()) {
+                output += &rewritten[last..url.start()];
+                output += &new_url;
+                last = url.end();
+            }
+        }
+
+        output += &rewritten[last..];
+        rewritten = output;
+    }
+
+    rewritten
+}
+
+/// local image references whose files do not exist
+pub fn missing_image_refs(slide: &Slide, contents: &str) -> Vec<String> {
+    scan_image_usages(contents)
+        .into_iter()
+        .filter(|usage| usage.is_local() && !usage.local_path(&slide.dir).is_file())
+        .map(|usage| usage.url)
+        .unique()
+        .collect()
+}
+
+fn collect_image_refs(slide: &Slide, contents: &str) -> anyhow::Result<Vec<ImageRef>> {
+    let mut refs = Vec::new();
+
+    for usage in scan_image_usages(contents) {
+        push_image_ref(slide, &mut refs, &usage)?;
     }
 
     refs.sort_by(|a, b| a.original.cmp(&b.original));
@@ -146,17 +253,17 @@ fn collect_image_refs(slide: &Slide, contents: &str) -> anyhow::Result<Vec<Image
     Ok(refs)
 }
 
-fn push_image_ref(slide: &Slide, refs: &mut Vec<ImageRef>, caps: &Captures) -> anyhow::Result<()> {
-    let Some(url) = caps.name("url").map(|m| m.as_str()) else {
-        return Ok(());
-    };
-
-    if should_skip_url(url) {
+fn push_image_ref(
+    slide: &Slide,
+    refs: &mut Vec<ImageRef>,
+    usage: &ImageUsage,
+) -> anyhow::Result<()> {
+    if !usage.is_local() {
         return Ok(());
     }
 
-    let path_part = url.split(['?', '#']).next().unwrap_or(url);
-    let path = slide.dir.join(path_part);
+    let url = usage.url.as_str();
+    let path = usage.local_path(&slide.dir);
     if !path.exists() || !path.is_file() {
         return Ok(());
     }
@@ -169,7 +276,7 @@ fn push_image_ref(slide: &Slide, refs: &mut Vec<ImageRef>, caps: &Captures) -> a
     Ok(())
 }
 
-fn should_skip_url(url: &str) -> bool {
+pub(crate) fn should_skip_url(url: &str) -> bool {
     url.starts_with("http://")
         || url.starts_with("https://")
         || url.starts_with("data:")
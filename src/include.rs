@@ -0,0 +1,555 @@
+//! include (`<!-- include: path/to/fragment.md -->`) and snippet
+//! (`<!-- snippet: path/to/main.rs#L10-L40 -->`) directives
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use regex::Regex;
+
+use crate::{
+    contents::Fences,
+    images::{map_image_urls, relative_path, scan_image_usages, should_skip_url},
+    project::Project,
+    slide::Slide,
+};
+
+/// expand include and snippet directives in the slide source
+///
+/// Image paths in included fragments are rewritten relative to `slide_dir`.
+pub fn expand_includes(slide_dir: &Path, contents: &str) -> anyhow::Result<String> {
+    let slide_dir = absolute_dir(slide_dir)?;
+    let mut stack = vec![];
+    let mut included = vec![];
+    expand_inner(&slide_dir, &slide_dir, contents, &mut stack, &mut included)
+}
+
+/// files included by the slide source, including nested includes and snippet sources
+pub fn included_files(slide_dir: &Path, contents: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let slide_dir = absolute_dir(slide_dir)?;
+    let mut stack = vec![];
+    let mut included = vec![];
+    expand_inner(&slide_dir, &slide_dir, contents, &mut stack, &mut included)?;
+    Ok(included)
+}
+
+fn expand_inner(
+    slide_dir: &Path,
+    base_dir: &Path,
+    contents: &str,
+    stack: &mut Vec<PathBuf>,
+    included: &mut Vec<PathBuf>,
+) -> anyhow::Result<String> {
+    let directive = Regex::new(r"^\s*<!--\s*include:\s*(.+?)\s*-->\s*$").unwrap();
+    let snippet = Regex::new(r"^\s*<!--\s*snippet:\s*(.+?)\s*-->\s*$").unwrap();
+    let mut fences = Fences::default();
+    let mut lines = vec![];
+
+    // own images of the file, before nested fragments are spliced in
+    let contents = rewrite_fragment_images(slide_dir, base_dir, contents);
+
+    for line in contents.lines() {
+        fences.toggles(line);
+
+        if let Some(caps) = snippet.captures(line).filter(|_| !fences.is_open()) {
+            let (path, code) = expand_snippet(base_dir, &caps[1])?;
+            if !included.contains(&path) {
+                included.push(path);
+            }
+            lines.push(code);
+            continue;
+        }
+
+        let Some(caps) = directive.captures(line).filter(|_| !fences.is_open()) else {
+            lines.push(line.to_string());
+            continue;
+        };
+
+        let path = base_dir.join(&caps[1]);
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Included file does not exist: {}", path.display()))?;
+
+        if let Some(start) = stack.iter().position(|p| *p == path) {
+            let cycle = stack[start..]
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>();
+            bail!("Include cycle detected: {}", cycle.join(" -> "));
+        }
+
+        let fragment = fs::read_to_string(&path)?;
+        let fragment_dir = path.parent().unwrap_or(base_dir).to_path_buf();
+
+        if !included.contains(&path) {
+            included.push(path.clone());
+        }
+
+        stack.push(path);
+        let expanded = expand_inner(slide_dir, &fragment_dir, &fragment, stack, included)?;
+        stack.pop();
+
+        lines.push(expanded.trim().to_string());
+    }
+
+    let mut expanded = lines.join("\n");
+    if contents.ends_with('\n') {
+        expanded.push('\n');
+    }
+
+    Ok(expanded)
+}
+
+/// rewrite relative include and snippet paths of contents moved from `from_dir` to `to_dir`
+///
+/// Used when a slide file is archived into (or restored from) a version directory, so
+/// the directives keep pointing at the same files. Paths inside `from_dir` are kept as they
+/// are, since those files are copied along with the slide file.
+pub fn rebase_directives(contents: &str, from_dir: &Path, to_dir: &Path) -> String {
+    let directive = Regex::new(r"^\s*<!--\s*(?:include|snippet):\s*(.+?)\s*-->\s*$").unwrap();
+    let mut fences = Fences::default();
+    let mut rebased = String::new();
+
+    for line in contents.split_inclusive('\n') {
+        fences.toggles(line);
+
+        let caps = directive
+            .captures(line.trim_end_matches(['\r', '\n']))
+            .filter(|_| !fences.is_open());
+        let Some(args) = caps.and_then(|caps| caps.get(1)) else {
+            rebased += line;
+            continue;
+        };
+
+        let target = args.as_str().split_whitespace().next().unwrap_or_default();
+        let file = target.split('#').next().unwrap_or_default();
+        if file.is_empty() || Path::new(file).is_absolute() {
+            rebased += line;
+            continue;
+        }
+
+        let target = normalize(&from_dir.join(file));
+        if target.starts_with(normalize(from_dir)) {
+            rebased += line;
+            continue;
+        }
+
+        let path = relative_path(to_dir, &target);
+        rebased += &line[..args.start()];
+        rebased += &path.to_string_lossy();
+        rebased += &line[args.start() + file.len()..];
+    }
+
+    rebased
+}
+
+/// expand `path[#L10-L40|#region] [lang=rust]` into a fenced code block
+fn expand_snippet(base_dir: &Path, args: &str) -> anyhow::Result<(PathBuf, String)> {
+    let mut args = args.split_whitespace();
+    let target = args.next().unwrap_or_default();
+    let (file, selector) = target.split_once('#').unwrap_or((target, ""));
+
+    let path = base_dir.join(file);
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Snippet file does not exist: {}", path.display()))?;
+    let source = fs::read_to_string(&path)?;
+    let source_lines = source.lines().collect::<Vec<_>>();
+
+    let lang = args
+        .find_map(|arg| arg.strip_prefix("lang="))
+        .map(String::from)
+        .unwrap_or_else(|| snippet_lang(&path));
+
+    let selected = if selector.is_empty() {
+        source_lines
+    } else if let Some((start, end)) = parse_line_range(selector) {
+        if start == 0 || start > end || end > source_lines.len() {
+            bail!(
+                "Snippet range {selector} is out of {} ({} lines)",
+                path.display(),
+                source_lines.len()
+            );
+        }
+        source_lines[start - 1..end].to_vec()
+    } else {
+        snippet_region(&source_lines, selector).with_context(|| {
+            format!(
+                "Snippet region `{selector}` not found in {}",
+                path.display()
+            )
+        })?
+    };
+
+    let code = dedent(&selected);
+    let fence = fence_for(&code);
+    Ok((path, format!("{fence}{lang}\n{code}\n{fence}")))
+}
+
+/// backtick fence longer than any backtick run in the code
+fn fence_for(code: &str) -> String {
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// parse `L10-L40`, `L10-40` or `L10`
+fn parse_line_range(selector: &str) -> Option<(usize, usize)> {
+    let range = Regex::new(r"^L(\d+)(?:-L?(\d+))?$").unwrap();
+    let caps = range.captures(selector)?;
+    let start = caps[1].parse().ok()?;
+    let end = caps
+        .get(2)
+        .map_or(Some(start), |m| m.as_str().parse().ok())?;
+    Some((start, end))
+}
+
+/// lines between `region: name` and `endregion: name` markers
+///
+/// Marker lines of other regions inside the region are dropped.
+fn snippet_region<'a>(lines: &[&'a str], name: &str) -> Option<Vec<&'a str>> {
+    let marker = Regex::new(r"\b(end)?region:\s*([\w-]+)").unwrap();
+    let start = lines.iter().position(|line| {
+        marker
+            .captures(line)
+            .is_some_and(|caps| caps.get(1).is_none() && &caps[2] == name)
+    })?;
+    let end = lines[start..].iter().position(|line| {
+        marker
+            .captures(line)
+            .is_some_and(|caps| caps.get(1).is_some() && &caps[2] == name)
+    })? + start;
+
+    Some(
+        lines[start + 1..end]
+            .iter()
+            .filter(|line| !marker.is_match(line))
+            .copied()
+            .collect(),
+    )
+}
+
+/// remove the common indentation of non-empty lines
+///
+/// The indentation is counted in whitespace characters, so a full-width space is one.
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| {
+            let mut rest = *line;
+            for _ in 0..indent {
+                let mut chars = rest.chars();
+                match chars.next() {
+                    Some(c) if c.is_whitespace() => rest = chars.as_str(),
+                    _ => break,
+                }
+            }
+            rest.trim_end()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// code block language from the file extension
+fn snippet_lang(path: &Path) -> String {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    match extension {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" => "typescript",
+        "rb" => "ruby",
+        "sh" => "bash",
+        "hs" => "haskell",
+        "kt" => "kotlin",
+        "md" => "markdown",
+        "yml" => "yaml",
+        ext => ext,
+    }
+    .to_string()
+}
+
+/// rewrite local image paths of a fragment relative to the slide directory
+fn rewrite_fragment_images(slide_dir: &Path, fragment_dir: &Path, contents: &str) -> String {
+    if slide_dir == fragment_dir {
+        return contents.to_string();
+    }
+
+    map_image_urls(contents, |url| {
+        if should_skip_url(url) {
+            return None;
+        }
+
+        let path = normalize(&fragment_dir.join(url));
+        Some(
+            relative_path(slide_dir, &path)
+                .to_string_lossy()
+                .to_string(),
+        )
+    })
+}
+
+/// local images outside the slide directory, i.e. images of included fragments
+///
+/// Rendered diagrams in the image cache are not included.
+pub fn fragment_images(
+    project: &Project,
+    slide: &Slide,
+    contents: &str,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let slide_dir = absolute_dir(&slide.dir)?;
+    let cache_dir = normalize(&project.root_dir.join(&project.conf.images.cache_dir));
+    let mut images = vec![];
+
+    for usage in scan_image_usages(contents) {
+        let path = usage.local_path(&slide.dir);
+        if !usage.is_local() || !path.is_file() {
+            continue;
+        }
+
+        let path = path.canonicalize()?;
+        if path.starts_with(&slide_dir) || normalize(&path).starts_with(&cache_dir) {
+            continue;
+        }
+        if !images.contains(&path) {
+            images.push(path);
+        }
+    }
+
+    Ok(images)
+}
+
+/// path of a fragment image in the HTML images directory
+///
+/// `include/` followed by the path relative to the project root, so images of
+/// different fragments do not collide with each other or with the slide's own images.
+pub fn fragment_image_path(project: &Project, image: &Path) -> PathBuf {
+    let root_dir = project
+        .root_dir
+        .canonicalize()
+        .unwrap_or_else(|_| project.root_dir.clone());
+    let relative = match image.strip_prefix(&root_dir) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => PathBuf::from(image.file_name().unwrap_or_default()),
+    };
+
+    PathBuf::from("include").join(relative)
+}
+
+/// point fragment images to where they are published under `images_dir`
+pub fn link_fragment_images(
+    project: &Project,
+    slide: &Slide,
+    contents: &str,
+    images_dir: &Path,
+) -> anyhow::Result<String> {
+    let images = fragment_images(project, slide, contents)?;
+    if images.is_empty() {
+        return Ok(contents.to_string());
+    }
+
+    Ok(map_image_urls(contents, |url| {
+        if should_skip_url(url) {
+            return None;
+        }
+
+        let (path, suffix) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+        let path = slide.dir.join(path).canonicalize().ok()?;
+        if !images.contains(&path) {
+            return None;
+        }
+
+        let published = images_dir.join(fragment_image_path(project, &path));
+        Some(format!("{}{suffix}", published.to_string_lossy()))
+    }))
+}
+
+/// resolve `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn absolute_dir(dir: &Path) -> anyhow::Result<PathBuf> {
+    dir.canonicalize()
+        .with_context(|| format!("Directory does not exist: {}", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{dedent, expand_includes, included_files, parse_line_range, rebase_directives};
+
+    #[test]
+    fn test_expand_includes_rewrites_image_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let slide_dir = root.join("src/intro");
+        let shared_dir = root.join("shared");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        std::fs::create_dir_all(shared_dir.join("images")).unwrap();
+        std::fs::write(
+            shared_dir.join("about.md"),
+            "# About me\n\n![me](images/me.png)\n\n---\n\n<!-- include: closing.md -->\n",
+        )
+        .unwrap();
+        std::fs::write(shared_dir.join("closing.md"), "# Thanks\n").unwrap();
+
+        let source =
+            "---\nmarp: true\n---\n\n<!-- include: ../../shared/about.md -->\n\n---\n\n# Main\n";
+        let expanded = expand_includes(&slide_dir, source).unwrap();
+
+        assert_eq!(
+            expanded,
+            "---\nmarp: true\n---\n\n# About me\n\n![me](../../shared/images/me.png)\n\n---\n\n# Thanks\n\n---\n\n# Main\n"
+        );
+
+        let files = included_files(&slide_dir, source).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("shared/about.md"));
+        assert!(files[1].ends_with("shared/closing.md"));
+    }
+
+    #[test]
+    fn test_expand_nested_includes_rewrites_image_paths_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let slide_dir = root.join("src/intro");
+        let parts_dir = root.join("common/parts");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        std::fs::create_dir_all(&parts_dir).unwrap();
+        std::fs::write(
+            root.join("common/a.md"),
+            "![a](a.png)\n\n<!-- include: parts/b.md -->\n",
+        )
+        .unwrap();
+        std::fs::write(
+            parts_dir.join("b.md"),
+            "![pic](pic.png)\n<img src=\"../up.png\">\n[pic.png.md](pic.png.md)\n",
+        )
+        .unwrap();
+
+        let expanded =
+            expand_includes(&slide_dir, "<!-- include: ../../common/a.md -->\n").unwrap();
+
+        assert_eq!(
+            expanded,
+            "![a](../../common/a.png)\n\n![pic](../../common/parts/pic.png)\n<img src=\"../../common/up.png\">\n[pic.png.md](pic.png.md)\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_includes_rejects_cycles() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("a.md"), "<!-- include: b.md -->\n").unwrap();
+        std::fs::write(root.join("b.md"), "<!-- include: a.md -->\n").unwrap();
+
+        let err = expand_includes(root, "<!-- include: a.md -->\n").unwrap_err();
+
+        assert!(err.to_string().starts_with("Include cycle detected"));
+    }
+
+    #[test]
+    fn test_expand_snippets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let slide_dir = root.join("src/intro");
+        let code_dir = root.join("code");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        std::fs::create_dir_all(&code_dir).unwrap();
+        std::fs::write(
+            code_dir.join("main.rs"),
+            "fn main() {\n    // region: greet\n    let name = \"world\";\n    println!(\"hello {name}\");\n    // endregion: greet\n}\n",
+        )
+        .unwrap();
+
+        let source = "# Code\n\n<!-- snippet: ../../code/main.rs#L3-L4 -->\n\n<!-- snippet: ../../code/main.rs#greet lang=rs -->\n\n```md\n<!-- snippet: ../../code/main.rs -->\n```\n";
+        let expanded = expand_includes(&slide_dir, source).unwrap();
+
+        assert_eq!(
+            expanded,
+            "# Code\n\n```rust\nlet name = \"world\";\nprintln!(\"hello {name}\");\n```\n\n```rs\nlet name = \"world\";\nprintln!(\"hello {name}\");\n```\n\n```md\n<!-- snippet: ../../code/main.rs -->\n```\n"
+        );
+
+        let files = included_files(&slide_dir, source).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("code/main.rs"));
+
+        let err =
+            expand_includes(&slide_dir, "<!-- snippet: ../../code/main.rs#L5-L9 -->").unwrap_err();
+        assert!(err.to_string().starts_with("Snippet range L5-L9 is out of"));
+        let err =
+            expand_includes(&slide_dir, "<!-- snippet: ../../code/main.rs#setup -->").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Snippet region `setup` not found"));
+    }
+
+    #[test]
+    fn test_rebase_directives() {
+        let source = "<!-- include: ../../shared/about.md -->\n\n```markdown\n<!-- include: fragment.md -->\n```\n\n<!-- snippet: code/main.rs#L1-L2 lang=rust -->\n";
+        let slide_dir = Path::new("/project/src/intro");
+        let archive_dir = slide_dir.join("v1");
+
+        let rebased = rebase_directives(source, slide_dir, &archive_dir);
+
+        assert_eq!(
+            rebased,
+            "<!-- include: ../../../shared/about.md -->\n\n```markdown\n<!-- include: fragment.md -->\n```\n\n<!-- snippet: code/main.rs#L1-L2 lang=rust -->\n"
+        );
+        assert_eq!(rebase_directives(&rebased, &archive_dir, slide_dir), source);
+    }
+
+    #[test]
+    fn test_dedent_by_whitespace_characters() {
+        assert_eq!(dedent(&["    a", "  b", "", "      c"]), "  a\nb\n\n    c");
+        assert_eq!(dedent(&["\u{3000}\u{3000}a", "  b"]), "a\nb");
+        assert_eq!(dedent(&["\u{3000}a", " \u{3000}b"]), "a\n\u{3000}b");
+    }
+
+    #[test]
+    fn test_snippet_fence_is_longer_than_backtick_runs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("README.md"), "# Usage\n\n```bash\nrun\n```\n").unwrap();
+
+        let expanded = expand_includes(root, "<!-- snippet: README.md -->\n").unwrap();
+
+        assert_eq!(
+            expanded,
+            "````markdown\n# Usage\n\n```bash\nrun\n```\n````\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_line_range() {
+        assert_eq!(parse_line_range("L10-L40"), Some((10, 40)));
+        assert_eq!(parse_line_range("L10-40"), Some((10, 40)));
+        assert_eq!(parse_line_range("L7"), Some((7, 7)));
+        assert_eq!(parse_line_range("setup"), None);
+    }
+}
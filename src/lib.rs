@@ -1,9 +1,16 @@
+pub mod bibtex;
+pub mod citation;
 pub mod config;
 pub mod contents;
+pub mod csl;
+pub mod diagram;
+pub mod diff;
 pub mod images;
+pub mod include;
 pub mod parser;
 pub mod path;
 pub mod project;
 pub mod slide;
 pub mod subcommand;
 pub mod template;
+pub mod variables;
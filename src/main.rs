@@ -1,36 +1,42 @@
 use clap::Parser;
 use slide_flow::{
-    config::PathStrategy,
+    config::{PathStrategy, TocMode},
     images::{clean_image_cache, optimize_slide_images, print_report, OptimizeOptions},
     parser::{
-        Cmd, ImagesCommands, MigrateCommands, OptionalTargetArgs, ProjectCommands, PruneCommands,
-        RequiredTargetArgs, SlidesCommands,
+        BibCommands, Cmd, ImagesCommands, MigrateCommands, OptionalTargetArgs, ProjectCommands,
+        PruneCommands, RequiredTargetArgs, SlidesCommands,
         SubCommands::{
-            Bib, Build, Images, Init, Migrate, Prepare, Project as ProjectCmd, Prune, Slide, Toc,
+            Bib, Build, Images, Init, Lint, Migrate, Prepare, Project as ProjectCmd, Prune, Slide,
+            Toc,
         },
     },
     project::Project,
     slide::Slide as SlideData,
     subcommand::{
         add::add,
-        bib::update_bibliography,
+        bib::{
+            add_bib_entry, check_bibliography, export_bibtex, import_bibtex, update_bibliography,
+        },
         build::{
             build, build_html_commands_with_options, build_ogp_image_commands_with_options,
             build_pdf_commands_with_options, build_pdf_latest_alias_commands_with_options,
             copy_images_html_with_options, copy_ipe_pdf, write_alias_redirects,
         },
-        index::put_index,
+        compare::diff_versions,
+        index::{check_index, index_titles, put_index},
         init::init,
+        lint::lint,
         list::list,
         migrate::{apply, plan, status, ApplyOptions},
         pre_commit::{prune_stale_outputs, refresh_project_files},
         slide::show,
-        version::bump,
+        stats::stats,
+        version::{bump, restore, Presentation, RestoreMode},
     },
 };
 use std::{
     collections::BTreeSet,
-    io::Write,
+    io::{IsTerminal, Write},
     path::{Path, PathBuf},
     process::Command,
 };
@@ -105,13 +111,61 @@ fn runner() -> anyhow::Result<()> {
                 },
             )
         }
-        Toc { targets, quiet } => {
+        Toc {
+            targets,
+            quiet,
+            check,
+        } => {
             let slides = resolve_required_targets(&project, &targets)?;
-            update_toc(&slides, quiet)
+            if check {
+                check_toc(&project, &slides)
+            } else {
+                update_toc(&project, &slides, quiet)
+            }
         }
-        Bib { targets } => {
+        Bib {
+            command: Some(command),
+            ..
+        } => match command {
+            BibCommands::Import { dir, file, keys } => {
+                let slide = project.get_slide(&dir)?;
+                import_bibtex(&slide, &file, &keys)
+            }
+            BibCommands::Add {
+                dir,
+                identifier,
+                tag,
+            } => {
+                let slide = project.get_slide(&dir)?;
+                add_bib_entry(&project, &slide, &identifier, tag.as_deref())
+            }
+            BibCommands::Export { dir, output } => {
+                let slide = project.get_slide(&dir)?;
+                let bibtex = export_bibtex(&slide);
+                match output {
+                    Some(output) => Ok(std::fs::write(output, bibtex)?),
+                    None => {
+                        print!("{bibtex}");
+                        Ok(())
+                    }
+                }
+            }
+        },
+        Bib {
+            command: None,
+            targets,
+            check,
+        } => {
             let slides = resolve_required_targets(&project, &targets)?;
-            update_bib(&slides)
+            if check {
+                check_bib(&project, &slides)
+            } else {
+                update_bib(&project, &slides)
+            }
+        }
+        Lint { targets, format } => {
+            let slides = resolve_required_targets(&project, &targets)?;
+            lint(&project, &slides, format)
         }
         Prune { command } => match command {
             PruneCommands::Outputs { dry_run, apply } => {
@@ -183,11 +237,91 @@ fn runner() -> anyhow::Result<()> {
                 type_.unwrap_or_default(),
             ),
             SlidesCommands::Show { selector } => show(&project, &selector),
-            SlidesCommands::Archive { dir } => bump(&project, dir),
+            SlidesCommands::Stats { dir, all, wpm } => {
+                let slides = match dir {
+                    Some(dir) if !all => vec![project.get_slide(&dir)?],
+                    _ => project.slides.clone(),
+                };
+                let mut rates = project.conf.stats.clone();
+                if let Some(wpm) = wpm {
+                    rates.words_per_minute = wpm;
+                }
+                stats(&project, &slides, !all, &rates)
+            }
+            SlidesCommands::Archive {
+                dir,
+                presented_at,
+                event,
+                notes,
+            } => {
+                let mut presentation = Presentation {
+                    presented_at,
+                    event,
+                    notes,
+                };
+                if presentation.is_empty() && std::io::stdin().is_terminal() {
+                    println!("Presentation of the archived version (leave empty to skip)");
+                    presentation = Presentation {
+                        presented_at: ask("Presented at (YYYY-MM-DD)")?,
+                        event: ask("Event")?,
+                        notes: ask("Notes")?,
+                    };
+                }
+                bump(&project, dir, presentation)
+            }
+            SlidesCommands::Restore {
+                dir,
+                from,
+                replace,
+                yes,
+            } => {
+                let mode = if replace {
+                    let question = format!(
+                        "Replace the working copy of {} with v{from}?",
+                        dir.display()
+                    );
+                    if !yes && !confirm(&question)? {
+                        anyhow::bail!("restore cancelled");
+                    }
+                    RestoreMode::Replace
+                } else {
+                    RestoreMode::NewVersion
+                };
+                restore(&project, dir, from, mode)
+            }
+            SlidesCommands::Diff {
+                dir,
+                from,
+                to,
+                format,
+            } => diff_versions(&project, &dir, from, to, format),
         },
     }
 }
 
+/// ask a yes/no question on the terminal
+fn confirm(question: &str) -> anyhow::Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// ask for a line on the terminal; empty answers are `None`
+fn ask(question: &str) -> anyhow::Result<Option<String>> {
+    print!("{question}: ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    let answer = answer.trim();
+    Ok((!answer.is_empty()).then(|| answer.to_string()))
+}
+
 struct PrepareOptions {
     refresh: bool,
     toc: bool,
@@ -241,8 +375,7 @@ fn changed_slides(project: &Project) -> anyhow::Result<Vec<SlideData>> {
         .arg(&project.root_dir)
         .arg("status")
         .arg("--porcelain")
-        .arg("--")
-        .arg("src")
+        .arg("--untracked-files=all")
         .output()
         .map_err(|e| anyhow::anyhow!("failed to run git status: {e}"))?;
 
@@ -256,6 +389,7 @@ fn changed_slides(project: &Project) -> anyhow::Result<Vec<SlideData>> {
 
     let stdout = String::from_utf8(output.stdout)?;
     let mut dirs = BTreeSet::new();
+    let mut changed_files = vec![];
 
     for line in stdout.lines() {
         let Some(path) = line.get(3..) else {
@@ -267,6 +401,9 @@ fn changed_slides(project: &Project) -> anyhow::Result<Vec<SlideData>> {
             .unwrap_or(path)
             .trim();
         let path = Path::new(path);
+        let absolute = project.root_dir.join(path);
+        changed_files.push(absolute.canonicalize().unwrap_or(absolute));
+
         let mut components = path.components();
 
         if components.next().and_then(|c| c.as_os_str().to_str()) != Some("src") {
@@ -280,6 +417,32 @@ fn changed_slides(project: &Project) -> anyhow::Result<Vec<SlideData>> {
         dirs.insert(PathBuf::from("src").join(slide_name.as_os_str()));
     }
 
+    // slides whose included fragments changed
+    for slide in &project.slides {
+        let Ok(dir) = slide.dir.strip_prefix(&project.root_dir) else {
+            continue;
+        };
+        if dirs.contains(dir) {
+            continue;
+        }
+
+        // a fragment that can no longer be resolved (e.g. deleted or renamed) is a change too
+        let changed = match project.slide_dependencies(slide) {
+            Ok(dependencies) => dependencies.iter().any(|dep| changed_files.contains(dep)),
+            Err(e) => {
+                log::warn!(
+                    "failed to resolve includes of {}: {}",
+                    slide.dir.display(),
+                    e
+                );
+                true
+            }
+        };
+        if changed {
+            dirs.insert(dir.to_path_buf());
+        }
+    }
+
     dirs.into_iter()
         .filter_map(|dir| match project.get_slide(&dir) {
             Ok(slide) => Some(Ok(slide)),
@@ -295,10 +458,18 @@ fn changed_slides(project: &Project) -> anyhow::Result<Vec<SlideData>> {
         .collect()
 }
 
-fn update_toc(slides: &[SlideData], quiet: bool) -> anyhow::Result<()> {
+fn update_toc(project: &Project, slides: &[SlideData], quiet: bool) -> anyhow::Result<()> {
     for slide in slides {
-        log::info!("Put index to slide: {}", slide.dir.to_string_lossy());
-        let toc = put_index(slide)?;
+        let toc = if project.conf.toc.mode == TocMode::Build {
+            // numbered at build time; only show the table of contents
+            let title_prefix = slide.conf.title_prefix.as_deref().unwrap_or("# ");
+            let section_prefix = slide.conf.section_prefix.as_deref();
+            let contents = std::fs::read_to_string(slide.slide_path())?;
+            index_titles(&contents, title_prefix, section_prefix, &project.conf.toc)?.1
+        } else {
+            log::info!("Put index to slide: {}", slide.dir.to_string_lossy());
+            put_index(slide, &project.conf.toc)?
+        };
         if !quiet {
             println!("{toc}");
         }
@@ -307,9 +478,48 @@ fn update_toc(slides: &[SlideData], quiet: bool) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn update_bib(slides: &[SlideData]) -> anyhow::Result<()> {
+fn update_bib(project: &Project, slides: &[SlideData]) -> anyhow::Result<()> {
     for slide in slides {
-        update_bibliography(slide.clone())?;
+        update_bibliography(project, slide.clone())?;
+    }
+
+    Ok(())
+}
+
+/// print the diffs `toc` would apply and fail if any slide is stale
+fn check_toc(project: &Project, slides: &[SlideData]) -> anyhow::Result<()> {
+    // `slide.md` is not indexed in the `build` mode
+    if project.conf.toc.mode == TocMode::Build {
+        return Ok(());
+    }
+
+    let mut stale = 0;
+    for slide in slides {
+        if let Some(diff) = check_index(slide, &project.conf.toc)? {
+            print!("{diff}");
+            stale += 1;
+        }
+    }
+
+    if stale > 0 {
+        anyhow::bail!("toc check failed: {stale} slide(s) would change");
+    }
+
+    Ok(())
+}
+
+/// print the diffs `bib` would apply and fail if any slide is stale
+fn check_bib(project: &Project, slides: &[SlideData]) -> anyhow::Result<()> {
+    let mut stale = 0;
+    for slide in slides {
+        if let Some(diff) = check_bibliography(project, slide)? {
+            print!("{diff}");
+            stale += 1;
+        }
+    }
+
+    if stale > 0 {
+        anyhow::bail!("bib check failed: {stale} slide(s) would change");
     }
 
     Ok(())
@@ -335,10 +545,10 @@ fn prepare(project: &Project, slides: &[SlideData], options: PrepareOptions) ->
         refresh_project_files(project)?;
     }
     if options.toc {
-        update_toc(slides, true)?;
+        update_toc(project, slides, true)?;
     }
     if options.bib {
-        update_bib(slides)?;
+        update_bib(project, slides)?;
     }
     if options.build {
         build_slides(project, slides, options.concurrent, options.optimize_images);
@@ -2,7 +2,7 @@
 
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 use crate::config::SlideType;
 
@@ -68,11 +68,28 @@ pub enum SubCommands {
         /// run quietly
         #[clap(short, long)]
         quiet: bool,
+        /// show the changes as a diff and fail if any, without writing
+        #[clap(long)]
+        check: bool,
     },
     /// Update slide bibliography
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Bib {
+        #[command(subcommand)]
+        command: Option<BibCommands>,
+        #[command(flatten)]
+        targets: RequiredTargetArgs,
+        /// show the changes as a diff and fail if any, without writing
+        #[clap(long)]
+        check: bool,
+    },
+    /// Check slides without building
+    Lint {
         #[command(flatten)]
         targets: RequiredTargetArgs,
+        /// output format
+        #[clap(long, value_enum, default_value = "human")]
+        format: ReportFormat,
     },
     /// Prune stale generated files
     #[clap(arg_required_else_help = true)]
@@ -120,6 +137,20 @@ pub struct RequiredTargetArgs {
     pub changed: bool,
 }
 
+#[derive(Debug, Clone, Args)]
+#[group(required = true, multiple = false)]
+pub struct BibIdentifierArgs {
+    /// DOI (e.g. 10.1093/comjnl/27.2.97)
+    #[clap(long)]
+    pub doi: Option<String>,
+    /// arXiv ID (e.g. 1706.03762)
+    #[clap(long)]
+    pub arxiv: Option<String>,
+    /// ISBN (needs `bibliography.isbn_resolver`)
+    #[clap(long)]
+    pub isbn: Option<String>,
+}
+
 #[derive(Debug, Clone, Args, Default)]
 #[group(multiple = false)]
 pub struct OptionalTargetArgs {
@@ -134,6 +165,13 @@ pub struct OptionalTargetArgs {
     pub changed: bool,
 }
 
+/// output format of reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Human,
+    Json,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum ProjectCommands {
     /// List managed slides
@@ -200,11 +238,113 @@ pub enum SlidesCommands {
         /// slide number from `slides list` or slide path like `src/intro`
         selector: String,
     },
+    /// Show page statistics and estimated speaking time
+    Stats {
+        /// slide directory (e.g. src/intro)
+        #[clap(required_unless_present = "all", conflicts_with = "all")]
+        dir: Option<PathBuf>,
+        /// show a table of all managed slides
+        #[clap(long)]
+        all: bool,
+        /// speaking rate in words per minute
+        #[clap(long)]
+        wpm: Option<u32>,
+    },
     /// bump slide version and archive current contents
+    ///
+    /// Asks for the presentation of the archived version on a terminal unless any of its flags
+    /// is given.
     Archive {
         /// slide directory (e.g. src/intro)
         #[clap(required = true)]
         dir: PathBuf,
+        /// date the archived version was presented (YYYY-MM-DD)
+        #[clap(long)]
+        presented_at: Option<String>,
+        /// event where the archived version was presented
+        #[clap(long)]
+        event: Option<String>,
+        /// notes on the archived version
+        #[clap(long)]
+        notes: Option<String>,
+    },
+    /// Restore an archived version into the working copy
+    Restore {
+        /// slide directory (e.g. src/intro)
+        dir: PathBuf,
+        /// archived version to restore (e.g. v2)
+        #[clap(long, value_parser = parse_version)]
+        from: u8,
+        /// replace the working copy instead of archiving it and restoring as a new version
+        #[clap(long)]
+        replace: bool,
+        /// replace without confirmation
+        #[clap(short, long, requires = "replace")]
+        yes: bool,
+    },
+    /// Show changes between two versions of a slide
+    Diff {
+        /// slide directory (e.g. src/intro)
+        dir: PathBuf,
+        /// old version (the latest archived version by default)
+        #[clap(value_parser = parse_version)]
+        from: Option<u8>,
+        /// new version (the working version by default)
+        #[clap(value_parser = parse_version)]
+        to: Option<u8>,
+        /// output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: DiffFormat,
+    },
+}
+
+/// output format of `slide diff`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiffFormat {
+    /// summary and page diffs
+    Text,
+    /// changelog for release notes
+    Markdown,
+}
+
+/// parse a slide version like `v2` or `2`
+fn parse_version(version: &str) -> Result<u8, String> {
+    version
+        .strip_prefix('v')
+        .unwrap_or(version)
+        .parse()
+        .map_err(|_| format!("invalid version: {version}"))
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BibCommands {
+    /// Import BibTeX entries into slide.toml
+    Import {
+        /// slide directory (e.g. src/intro)
+        dir: PathBuf,
+        /// BibTeX file
+        file: PathBuf,
+        /// import only these citation keys
+        #[clap(long, value_delimiter = ',')]
+        keys: Vec<String>,
+    },
+    /// Add an entry fetched by DOI, arXiv ID or ISBN to slide.toml
+    Add {
+        /// slide directory (e.g. src/intro)
+        dir: PathBuf,
+        #[command(flatten)]
+        identifier: BibIdentifierArgs,
+        /// citation tag (generated from the first author, year and title by default)
+        #[clap(long)]
+        tag: Option<String>,
+    },
+    /// Export slide bibliography as BibTeX
+    Export {
+        /// slide directory (e.g. src/intro)
+        dir: PathBuf,
+        /// output file (stdout by default)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
@@ -286,10 +426,60 @@ mod tests {
 
         match cmd.subcommand {
             SubCommands::Slide {
-                command: SlidesCommands::Archive { dir },
+                command: SlidesCommands::Archive { dir, .. },
             } => assert_eq!(dir, PathBuf::from("src/intro")),
             other => panic!("unexpected command: {other:?}"),
         }
+
+        let cmd = Cmd::try_parse_from([
+            "slide-flow",
+            "slide",
+            "archive",
+            "src/intro",
+            "--presented-at",
+            "2026-10-18",
+            "--event",
+            "RustConf",
+        ])
+        .unwrap();
+
+        match cmd.subcommand {
+            SubCommands::Slide {
+                command:
+                    SlidesCommands::Archive {
+                        presented_at,
+                        event,
+                        notes,
+                        ..
+                    },
+            } => {
+                assert_eq!(presented_at.as_deref(), Some("2026-10-18"));
+                assert_eq!(event.as_deref(), Some("RustConf"));
+                assert_eq!(notes, None);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_slide_stats_command() {
+        let cmd =
+            Cmd::try_parse_from(["slide-flow", "slide", "stats", "src/intro", "--wpm", "150"])
+                .unwrap();
+
+        match cmd.subcommand {
+            SubCommands::Slide {
+                command: SlidesCommands::Stats { dir, all, wpm },
+            } => {
+                assert_eq!(dir, Some(PathBuf::from("src/intro")));
+                assert!(!all);
+                assert_eq!(wpm, Some(150));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let err = Cmd::try_parse_from(["slide-flow", "slide", "stats"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
     }
 
     #[test]
@@ -309,11 +499,16 @@ mod tests {
         let cmd = Cmd::try_parse_from(["slide-flow", "toc", "src/intro", "--quiet"]).unwrap();
 
         match cmd.subcommand {
-            SubCommands::Toc { targets, quiet } => {
+            SubCommands::Toc {
+                targets,
+                quiet,
+                check,
+            } => {
                 assert_eq!(targets.directories, vec![PathBuf::from("src/intro")]);
                 assert!(!targets.all);
                 assert!(!targets.changed);
                 assert!(quiet);
+                assert!(!check);
             }
             other => panic!("unexpected command: {other:?}"),
         }
@@ -324,10 +519,198 @@ mod tests {
         let cmd = Cmd::try_parse_from(["slide-flow", "bib", "--all"]).unwrap();
 
         match cmd.subcommand {
-            SubCommands::Bib { targets } => {
+            SubCommands::Bib {
+                command: None,
+                targets,
+                check,
+            } => {
                 assert!(targets.directories.is_empty());
                 assert!(targets.all);
                 assert!(!targets.changed);
+                assert!(!check);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_slide_diff_command() {
+        let cmd = Cmd::try_parse_from([
+            "slide-flow",
+            "slide",
+            "diff",
+            "src/intro",
+            "v1",
+            "2",
+            "--format",
+            "markdown",
+        ])
+        .unwrap();
+
+        match cmd.subcommand {
+            SubCommands::Slide {
+                command:
+                    SlidesCommands::Diff {
+                        dir,
+                        from,
+                        to,
+                        format,
+                    },
+            } => {
+                assert_eq!(dir, PathBuf::from("src/intro"));
+                assert_eq!(from, Some(1));
+                assert_eq!(to, Some(2));
+                assert_eq!(format, DiffFormat::Markdown);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        assert!(Cmd::try_parse_from(["slide-flow", "slide", "diff", "src/intro", "vx"]).is_err());
+    }
+
+    #[test]
+    fn parses_slide_restore_command() {
+        let cmd = Cmd::try_parse_from([
+            "slide-flow",
+            "slide",
+            "restore",
+            "src/intro",
+            "--from",
+            "v2",
+            "--replace",
+            "-y",
+        ])
+        .unwrap();
+
+        match cmd.subcommand {
+            SubCommands::Slide {
+                command:
+                    SlidesCommands::Restore {
+                        dir,
+                        from,
+                        replace,
+                        yes,
+                    },
+            } => {
+                assert_eq!(dir, PathBuf::from("src/intro"));
+                assert_eq!(from, 2);
+                assert!(replace);
+                assert!(yes);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        assert!(Cmd::try_parse_from([
+            "slide-flow",
+            "slide",
+            "restore",
+            "src/intro",
+            "--from",
+            "v2",
+            "--yes"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_check_flags() {
+        let cmd = Cmd::try_parse_from(["slide-flow", "toc", "--all", "--check"]).unwrap();
+        assert!(matches!(
+            cmd.subcommand,
+            SubCommands::Toc { check: true, .. }
+        ));
+
+        let cmd = Cmd::try_parse_from(["slide-flow", "bib", "--changed", "--check"]).unwrap();
+        assert!(matches!(
+            cmd.subcommand,
+            SubCommands::Bib {
+                command: None,
+                check: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parses_bib_import_command() {
+        let cmd = Cmd::try_parse_from([
+            "slide-flow",
+            "bib",
+            "import",
+            "src/intro",
+            "refs.bib",
+            "--keys",
+            "a,b",
+        ])
+        .unwrap();
+
+        match cmd.subcommand {
+            SubCommands::Bib {
+                command: Some(BibCommands::Import { dir, file, keys }),
+                ..
+            } => {
+                assert_eq!(dir, PathBuf::from("src/intro"));
+                assert_eq!(file, PathBuf::from("refs.bib"));
+                assert_eq!(keys, vec!["a", "b"]);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        assert!(Cmd::try_parse_from(["slide-flow", "bib"]).is_err());
+    }
+
+    #[test]
+    fn parses_bib_add_command() {
+        let cmd = Cmd::try_parse_from([
+            "slide-flow",
+            "bib",
+            "add",
+            "src/intro",
+            "--arxiv",
+            "1706.03762",
+        ])
+        .unwrap();
+
+        match cmd.subcommand {
+            SubCommands::Bib {
+                command:
+                    Some(BibCommands::Add {
+                        dir,
+                        identifier,
+                        tag,
+                    }),
+                ..
+            } => {
+                assert_eq!(dir, PathBuf::from("src/intro"));
+                assert_eq!(identifier.arxiv.as_deref(), Some("1706.03762"));
+                assert_eq!(identifier.doi, None);
+                assert_eq!(tag, None);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        assert!(Cmd::try_parse_from(["slide-flow", "bib", "add", "src/intro"]).is_err());
+        assert!(Cmd::try_parse_from([
+            "slide-flow",
+            "bib",
+            "add",
+            "src/intro",
+            "--doi",
+            "10.1/x",
+            "--isbn",
+            "978-0",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_lint_json_command() {
+        let cmd = Cmd::try_parse_from(["slide-flow", "lint", "--all", "--format", "json"]).unwrap();
+
+        match cmd.subcommand {
+            SubCommands::Lint { targets, format } => {
+                assert!(targets.all);
+                assert_eq!(format, ReportFormat::Json);
             }
             other => panic!("unexpected command: {other:?}"),
         }
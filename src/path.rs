@@ -100,7 +100,7 @@ fn is_archived_slide(slide: &Slide) -> bool {
 #[cfg(test)]
 mod tests {
     use crate::{
-        config::{BuildConf, PathStrategy, ProjectConf, SlideConf, SlideType, TemplateConf},
+        config::{BuildConf, PathStrategy, ProjectConf, SlideConf},
         project::Project,
         slide::Slide,
     };
@@ -115,13 +115,12 @@ mod tests {
                 author: "author".to_string(),
                 base_url: "https://example.com".to_string(),
                 output_dir: "output".to_string(),
-                template: TemplateConf::default(),
                 build: BuildConf {
                     theme_dir: ".marp/themes".to_string(),
                     marp_binary: "marp".to_string(),
                     path_strategy,
                 },
-                images: Default::default(),
+                ..Default::default()
             },
             slides: vec![],
         }
@@ -135,12 +134,8 @@ mod tests {
                 version: 2,
                 secret: Some("uuid".to_string()),
                 custom_path: Some(vec!["talks".to_string()]),
-                draft: None,
-                description: None,
-                title_prefix: None,
-                type_: SlideType::Marp,
-                bibliography: None,
                 path_strategy,
+                ..Default::default()
             },
         }
     }
@@ -3,11 +3,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 use itertools::Itertools;
+use serde::Deserialize;
 
 use crate::{
-    config::{PathStrategy, ProjectConf, SlideConf},
+    bibtex::parse_bibtex,
+    config::{BibEntry, PathStrategy, ProjectConf, SlideConf},
     slide::Slide,
 };
 
@@ -129,6 +131,81 @@ impl Project {
             .unwrap_or(self.conf.build.path_strategy)
     }
 
+    /// path to the shared bibliography
+    pub fn shared_bibliography_path(&self) -> Option<PathBuf> {
+        self.conf
+            .bibliography
+            .path
+            .as_ref()
+            .map(|path| self.root_dir.join(path))
+    }
+
+    /// entries of the shared bibliography
+    pub fn shared_bibliography(&self) -> anyhow::Result<Vec<BibEntry>> {
+        let Some(path) = self.shared_bibliography_path() else {
+            return Ok(vec![]);
+        };
+        let contents = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "The shared bibliography does not exist: {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        if path.extension().is_some_and(|ext| ext == "bib") {
+            let entries = parse_bibtex(&contents)?
+                .into_iter()
+                .filter_map(|entry| match entry.to_bib_entry() {
+                    Ok(entry) => Some(entry),
+                    Err(e) => {
+                        log::warn!("skip shared bibliography entry: {e}");
+                        None
+                    }
+                })
+                .collect();
+            return Ok(entries);
+        }
+
+        #[derive(Deserialize)]
+        struct SharedBibliography {
+            #[serde(default)]
+            bibliography: Vec<BibEntry>,
+        }
+
+        let shared: SharedBibliography = toml::from_str(&contents)?;
+        Ok(shared.bibliography)
+    }
+
+    /// bibliography available to the slide
+    ///
+    /// Slide entries take precedence over shared entries with the same tag.
+    pub fn bibliography(&self, slide: &Slide) -> anyhow::Result<Vec<BibEntry>> {
+        let mut entries = slide.conf.bibliography.clone().unwrap_or_default();
+        for entry in self.shared_bibliography()? {
+            if !entries.iter().any(|e| e.tag == entry.tag) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// files other than the slide source that the slide depends on
+    ///
+    /// Included files and the shared bibliography.
+    pub fn slide_dependencies(&self, slide: &Slide) -> anyhow::Result<Vec<PathBuf>> {
+        let mut dependencies = slide.dependencies()?;
+
+        if let Some(path) = self
+            .shared_bibliography_path()
+            .filter(|_| slide.conf.type_.is_marp())
+        {
+            dependencies.push(path.canonicalize().unwrap_or(path));
+        }
+
+        Ok(dependencies)
+    }
+
     /// get archived versions of a slide (src/<slide>/v*)
     pub fn get_archived_slides(&self, slide: &Slide) -> anyhow::Result<Vec<Slide>> {
         let archived = fs::read_dir(&slide.dir)?
@@ -145,6 +222,14 @@ impl Project {
 
         Ok(archived)
     }
+
+    /// get an archived version of a slide (src/<slide>/v<version>)
+    pub fn get_archived_slide(&self, slide: &Slide, version: u8) -> anyhow::Result<Slide> {
+        self.get_archived_slides(slide)?
+            .into_iter()
+            .find(|archived| archived.conf.version == version)
+            .with_context(|| format!("Version v{version} of {} does not exist", slide.conf.name))
+    }
 }
 
 fn split_versioned_alias(path: &Path) -> Option<(String, u8)> {
@@ -206,6 +291,7 @@ mod tests {
         bump(
             &Project::get(root.to_path_buf()).unwrap(),
             "src/intro".into(),
+            Default::default(),
         )
         .unwrap();
 
@@ -233,6 +319,7 @@ mod tests {
         bump(
             &Project::get(root.to_path_buf()).unwrap(),
             "src/intro".into(),
+            Default::default(),
         )
         .unwrap();
 
@@ -242,4 +329,44 @@ mod tests {
         assert_eq!(slide.conf.version, 2);
         assert!(slide.dir.to_string_lossy().ends_with("src/intro"));
     }
+
+    #[test]
+    fn test_bibliography_merges_shared_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        init(root).unwrap();
+        let conf = std::fs::read_to_string(root.join("config.toml")).unwrap();
+        std::fs::write(
+            root.join("config.toml"),
+            conf.replace("[bibliography]\n", "[bibliography]\npath = \"refs.bib\"\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("refs.bib"),
+            "@misc{shared, title={Shared}, year=2020}\n@misc{local, title={Shared Local}, year=2021}\n",
+        )
+        .unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+        let conf_path = root.join("src/intro/slide.toml");
+        let conf = std::fs::read_to_string(&conf_path).unwrap().replace(
+            "bibliography = []",
+            "bibliography = [{ tag = \"local\", title = \"Local\", year = 2022 }]",
+        );
+        std::fs::write(&conf_path, conf).unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide(Path::new("src/intro")).unwrap();
+        let entries = project.bibliography(&slide).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].tag, "local");
+        assert_eq!(entries[0].title, "Local");
+        assert_eq!(entries[1].tag, "shared");
+
+        let dependencies = project.slide_dependencies(&slide).unwrap();
+        assert!(dependencies[0].ends_with("refs.bib"));
+    }
 }
@@ -1,6 +1,10 @@
 use std::path::PathBuf;
 
-use crate::{config::SlideConf, contents::SlideContents};
+use crate::{
+    config::SlideConf,
+    contents::SlideContents,
+    include::{expand_includes, included_files},
+};
 
 /// project information
 #[derive(Debug, Clone)]
@@ -29,4 +33,22 @@ impl Slide {
 
         SlideContents::try_from(slide_str.as_str())
     }
+
+    /// get markdown source with include directives expanded
+    pub fn get_expanded_source(&self) -> anyhow::Result<String> {
+        let slide_str = std::fs::read_to_string(self.slide_path())?;
+
+        expand_includes(&self.dir, &slide_str)
+    }
+
+    /// files included by the slide source (fragments and snippet sources)
+    pub fn dependencies(&self) -> anyhow::Result<Vec<PathBuf>> {
+        if !self.conf.type_.is_marp() {
+            return Ok(vec![]);
+        }
+
+        let slide_str = std::fs::read_to_string(self.slide_path())?;
+
+        included_files(&self.dir, &slide_str)
+    }
 }
@@ -7,6 +7,8 @@ use anyhow::bail;
 use crate::{
     config::{PathStrategy, SlideConf, SlideType},
     project::Project,
+    slide::Slide,
+    variables::Variables,
 };
 
 pub fn add(
@@ -31,13 +33,6 @@ pub fn add(
     fs::create_dir(&images_dir)?;
     fs::write(images_dir.join(".gitignore"), "")?;
 
-    // make slide file
-    let slide_path = slides_dir.join(type_.file_name());
-    if type_.is_marp() {
-        log::info!("Created a new slide: {}", slide_path.to_string_lossy());
-        fs::write(&slide_path, &project.conf.template.slide)?;
-    }
-
     // make config file
     let conf = SlideConf {
         name,
@@ -47,6 +42,10 @@ pub fn add(
         draft: draft.then_some(true),
         description: Some(String::new()),
         title_prefix: None,
+        section_prefix: None,
+        presented_at: None,
+        event: None,
+        notes: None,
         type_,
         bibliography: Some(vec![]),
         path_strategy: Some(PathStrategy::CanonicalWithRedirects),
@@ -57,5 +56,17 @@ pub fn add(
 
     fs::write(conf_path, conf_str)?;
 
+    // make slide file
+    let slide = Slide {
+        dir: slides_dir,
+        conf,
+    };
+    let slide_path = slide.slide_path();
+    if slide.conf.type_.is_marp() {
+        log::info!("Created a new slide: {}", slide_path.to_string_lossy());
+        let template = Variables::new(project, &slide).expand(&project.conf.template.slide);
+        fs::write(&slide_path, template)?;
+    }
+
     Ok(())
 }
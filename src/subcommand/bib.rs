@@ -1,34 +1,506 @@
-use crate::slide::Slide;
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::{anyhow, bail, Context};
+use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table};
+
+use crate::{
+    bibtex::{parse_bibtex, to_bibtex},
+    config::{BibEntry, BibliographyConf, BibliographyMode},
+    contents::SlideContents,
+    csl::csl_to_bib_entry,
+    diff::unified_diff,
+    parser::BibIdentifierArgs,
+    project::Project,
+    slide::Slide,
+};
 
 /// modify bibliography
-pub fn update_bibliography(target_slide: Slide) -> anyhow::Result<()> {
-    let mut contents = target_slide.get_contents()?;
+pub fn update_bibliography(project: &Project, target_slide: Slide) -> anyhow::Result<()> {
+    let (_, new_contents) = bibliography_contents(project, &target_slide)?;
+
+    // save to slide file
+    std::fs::write(target_slide.slide_path(), new_contents)?;
 
     log::info!(
-        "Updating bibliography in slide: {}",
+        "Saved slide file: {}",
         target_slide.slide_path().to_string_lossy()
     );
 
-    let bib_entries = target_slide.conf.bibliography.as_deref().unwrap_or(&[]);
+    anyhow::Ok(())
+}
 
-    // modify bibliography
-    contents.modify_bibliography(bib_entries);
+/// unified diff of the changes `update_bibliography` would make, without writing
+pub fn check_bibliography(
+    project: &Project,
+    target_slide: &Slide,
+) -> anyhow::Result<Option<String>> {
+    let (contents, new_contents) = bibliography_contents(project, target_slide)?;
+
+    Ok(unified_diff(
+        &target_slide.slide_path(),
+        &contents,
+        &new_contents,
+    ))
+}
+
+/// current and updated contents of the slide file
+fn bibliography_contents(
+    project: &Project,
+    target_slide: &Slide,
+) -> anyhow::Result<(String, String)> {
+    let slide_path = target_slide.slide_path();
+    let current = fs::read_to_string(&slide_path)?;
+    let mut contents = SlideContents::try_from(current.as_str())?;
+
+    log::info!(
+        "Updating bibliography in slide: {}",
+        slide_path.to_string_lossy()
+    );
+
+    let bib_entries = project.bibliography(target_slide)?;
+
+    // modify bibliography; numbered at build time in the `build` mode
+    if project.conf.bibliography.mode == BibliographyMode::Source {
+        contents.modify_bibliography_with_style(&bib_entries, &project.conf.bibliography);
+    }
+
+    // number figures and tables
+    contents.modify_figures()?;
+
+    // resolve cross references between pages
+    contents.modify_cross_references()?;
 
     log::info!(
         "Modified bibliography in slide: {}",
-        target_slide.slide_path().to_string_lossy()
+        slide_path.to_string_lossy()
     );
 
     // new slide string
-    let new_contents = contents.to_marp();
+    Ok((current, contents.to_marp()))
+}
 
-    // save to slide file
-    std::fs::write(target_slide.slide_path(), new_contents)?;
+/// number citations and add footnotes to the build input of the slide
+///
+/// In the `source` mode, where `bib` already wrote them into `slide.md`, only the pages
+/// citing each entry in the reference lists are updated to the pages of the build input.
+pub fn render_bibliography(
+    project: &Project,
+    slide: &Slide,
+    contents: &str,
+) -> anyhow::Result<String> {
+    let bib_entries = project.bibliography(slide)?;
+    if bib_entries.is_empty() {
+        return Ok(contents.to_string());
+    }
+
+    let mut slide_contents = SlideContents::parse_without_frontmatter_check(contents)?;
+    match project.conf.bibliography.mode {
+        BibliographyMode::Build => {
+            slide_contents.modify_bibliography_with_style(&bib_entries, &project.conf.bibliography)
+        }
+        BibliographyMode::Source => {
+            slide_contents.update_citing_pages(&bib_entries, &project.conf.bibliography)
+        }
+    }
+
+    Ok(slide_contents.to_marp())
+}
+
+/// number figures and resolve cross references of the build input
+///
+/// Page numbers are taken from the expanded input, so they match the published deck even
+/// when includes or generated pages shift the pages of `slide.md`.
+pub fn render_cross_references(contents: &str) -> anyhow::Result<String> {
+    let mut slide_contents = SlideContents::parse_without_frontmatter_check(contents)?;
+    slide_contents.modify_figures()?;
+    slide_contents.modify_cross_references()?;
+
+    Ok(slide_contents.to_marp())
+}
+
+/// import BibTeX entries into `slide.toml`, merging by tag
+///
+/// Only entries in `keys` are imported when it is not empty.
+/// Other contents of `slide.toml`, including comments, are kept.
+pub fn import_bibtex(slide: &Slide, bib_path: &Path, keys: &[String]) -> anyhow::Result<()> {
+    let entries = parse_bibtex(&fs::read_to_string(bib_path)?)?;
+
+    let missing = keys
+        .iter()
+        .filter(|key| !entries.iter().any(|entry| &entry.key == *key))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        bail!(
+            "Keys not found in {}: {}",
+            bib_path.display(),
+            missing.join(", ")
+        );
+    }
+
+    let entries = entries
+        .iter()
+        .filter(|entry| keys.is_empty() || keys.contains(&entry.key))
+        .map(|entry| entry.to_bib_entry())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let conf_path = slide.dir.join("slide.toml");
+    let mut doc = fs::read_to_string(&conf_path)?.parse::<DocumentMut>()?;
+    let (added, updated) = merge_bib_entries(&mut doc, &entries)?;
+    fs::write(&conf_path, doc.to_string())?;
 
     log::info!(
-        "Saved slide file: {}",
-        target_slide.slide_path().to_string_lossy()
+        "Imported bibliography into {}: {} added, {} updated",
+        conf_path.to_string_lossy(),
+        added,
+        updated
     );
 
-    anyhow::Ok(())
+    Ok(())
+}
+
+/// fetch an entry by DOI, arXiv ID or ISBN from the resolver and add it to `slide.toml`
+pub fn add_bib_entry(
+    project: &Project,
+    slide: &Slide,
+    identifier: &BibIdentifierArgs,
+    tag: Option<&str>,
+) -> anyhow::Result<()> {
+    let url = resolver_url(&project.conf.bibliography, identifier)?;
+
+    log::info!("Fetching bibliography entry: {url}");
+
+    let csl = fetch_csl_json(&url)?;
+    let mut entry = csl_to_bib_entry(&csl, tag)?;
+    if let Some(arxiv) = &identifier.arxiv {
+        // the DOI of arXiv is only an alias of the arXiv ID
+        entry.doi = None;
+        entry.arxiv = Some(arxiv.trim_start_matches("arXiv:").to_string());
+    }
+
+    let exists = slide
+        .conf
+        .bibliography
+        .iter()
+        .flatten()
+        .any(|existing| existing.tag == entry.tag);
+    if exists {
+        bail!(
+            "Bibliography entry `{}` already exists in {}; choose another with --tag",
+            entry.tag,
+            slide.dir.display()
+        );
+    }
+
+    let conf_path = slide.dir.join("slide.toml");
+    let mut doc = fs::read_to_string(&conf_path)?.parse::<DocumentMut>()?;
+    merge_bib_entries(&mut doc, std::slice::from_ref(&entry))?;
+    fs::write(&conf_path, doc.to_string())?;
+
+    log::info!("Added `{}` to {}", entry.tag, conf_path.to_string_lossy());
+
+    Ok(())
+}
+
+/// URL of the CSL-JSON for the identifier
+fn resolver_url(conf: &BibliographyConf, identifier: &BibIdentifierArgs) -> anyhow::Result<String> {
+    if let Some(doi) = &identifier.doi {
+        let doi = doi
+            .trim_start_matches("https://doi.org/")
+            .trim_start_matches("doi:");
+        return Ok(format!("{}/{doi}", conf.resolver()));
+    }
+    if let Some(arxiv) = &identifier.arxiv {
+        let arxiv = arxiv.trim_start_matches("arXiv:");
+        return Ok(format!("{}/10.48550/arXiv.{arxiv}", conf.resolver()));
+    }
+    if let Some(isbn) = &identifier.isbn {
+        let Some(resolver) = &conf.isbn_resolver else {
+            bail!("Looking up ISBNs needs `bibliography.isbn_resolver` in config.toml");
+        };
+        let isbn = isbn.replace('-', "");
+        return Ok(format!("{}/{isbn}", resolver.trim_end_matches('/')));
+    }
+    bail!("One of --doi, --arxiv or --isbn is required")
+}
+
+fn fetch_csl_json(url: &str) -> anyhow::Result<serde_json::Value> {
+    let response = ureq::get(url)
+        .set("Accept", "application/vnd.citationstyles.csl+json")
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => anyhow!("Resolver returned {code} for {url}"),
+            e => anyhow!(e).context(format!("Failed to fetch {url}")),
+        })?;
+
+    response
+        .into_json()
+        .with_context(|| format!("Resolver returned invalid CSL-JSON for {url}"))
+}
+
+/// format bibliography of the slide as BibTeX
+pub fn export_bibtex(slide: &Slide) -> String {
+    slide
+        .conf
+        .bibliography
+        .as_deref()
+        .unwrap_or(&[])
+        .iter()
+        .map(to_bibtex)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// update `[[bibliography]]` tables in place; returns (added, updated)
+fn merge_bib_entries(
+    doc: &mut DocumentMut,
+    entries: &[BibEntry],
+) -> anyhow::Result<(usize, usize)> {
+    // `bibliography = []` written by `slide add`, or inline tables
+    if !doc
+        .get("bibliography")
+        .is_some_and(Item::is_array_of_tables)
+    {
+        let tables = match doc.remove("bibliography") {
+            None => ArrayOfTables::new(),
+            Some(item) if item.as_array().is_some_and(|array| array.is_empty()) => {
+                ArrayOfTables::new()
+            }
+            Some(item) => match item.into_array_of_tables() {
+                Ok(tables) => tables,
+                Err(_) => bail!("`bibliography` in slide.toml must be an array of tables"),
+            },
+        };
+        doc.insert("bibliography", Item::ArrayOfTables(tables));
+    }
+
+    let item = &mut doc["bibliography"];
+    let tables = item.as_array_of_tables_mut().unwrap();
+    let mut seen = HashSet::new();
+    let (mut added, mut updated) = (0, 0);
+
+    for entry in entries {
+        if !seen.insert(&entry.tag) {
+            continue;
+        }
+
+        let existing = tables
+            .iter_mut()
+            .find(|table| table.get("tag").and_then(Item::as_str) == Some(&entry.tag));
+
+        match existing {
+            Some(table) => {
+                set_bib_fields(table, entry);
+                updated += 1;
+            }
+            None => {
+                let mut table = Table::new();
+                table["tag"] = value(&entry.tag);
+                set_bib_fields(&mut table, entry);
+                tables.push(table);
+                added += 1;
+            }
+        }
+    }
+
+    Ok((added, updated))
+}
+
+fn set_bib_fields(table: &mut Table, entry: &BibEntry) {
+    if let Some(kind) = &entry.kind {
+        table["kind"] = value(kind);
+    }
+    table["title"] = value(&entry.title);
+    if let Some(authors) = &entry.authors {
+        table["authors"] = value(authors);
+    }
+    if let Some(year) = entry.year {
+        table["year"] = value(i64::from(year));
+    }
+
+    let optional = [
+        ("month", &entry.month),
+        ("venue", &entry.venue),
+        ("volume", &entry.volume),
+        ("pages", &entry.pages),
+        ("publisher", &entry.publisher),
+        ("doi", &entry.doi),
+        ("arxiv", &entry.arxiv),
+        ("note", &entry.note),
+        ("url", &entry.url),
+    ];
+    for (name, field) in optional {
+        if let Some(field) = field {
+            table[name] = value(field);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        path::Path,
+        thread,
+    };
+
+    use toml_edit::DocumentMut;
+
+    use crate::{
+        config::{BibEntry, SlideConf, SlideType},
+        parser::BibIdentifierArgs,
+        project::Project,
+        subcommand::{add::add, init::init},
+    };
+
+    use super::{add_bib_entry, check_bibliography, merge_bib_entries, update_bibliography};
+
+    #[test]
+    fn test_merge_bib_entries_keeps_comments() {
+        let mut doc = r#"# my talk
+name = "intro"
+version = 1
+bibliography = []
+path_strategy = "legacy"
+"#
+        .parse::<DocumentMut>()
+        .unwrap();
+        let entry = |tag: &str, title: &str| BibEntry {
+            tag: tag.to_string(),
+            title: title.to_string(),
+            authors: None,
+            year: Some(2020),
+            venue: None,
+            url: None,
+            ..Default::default()
+        };
+
+        let (added, updated) =
+            merge_bib_entries(&mut doc, &[entry("a", "First"), entry("b", "Second")]).unwrap();
+        assert_eq!((added, updated), (2, 0));
+
+        // edit by hand, then import again
+        let mut doc = doc
+            .to_string()
+            .replace("title = \"First\"", "title = \"First\" # keep me")
+            .parse::<DocumentMut>()
+            .unwrap();
+        let (added, updated) =
+            merge_bib_entries(&mut doc, &[entry("b", "Second (revised)")]).unwrap();
+        assert_eq!((added, updated), (0, 1));
+
+        let text = doc.to_string();
+        assert!(text.starts_with("# my talk\n"));
+        assert!(text.contains("title = \"First\" # keep me"));
+
+        let conf: SlideConf = toml::from_str(&text).unwrap();
+        let bibliography = conf.bibliography.unwrap();
+        assert_eq!(bibliography.len(), 2);
+        assert_eq!(bibliography[1].title, "Second (revised)");
+    }
+
+    #[test]
+    fn test_check_bibliography_keeps_formatting() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let slide_dir = root.join("src/intro");
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+
+        // no citations
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "---\nmarp: true\n---\n\n# Intro\n\n---\n\n# Method\n",
+        )
+        .unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide(&slide_dir).unwrap();
+        assert_eq!(check_bibliography(&project, &slide).unwrap(), None);
+
+        // numbered once, then up to date
+        let conf_path = slide_dir.join("slide.toml");
+        let conf = std::fs::read_to_string(&conf_path).unwrap().replace(
+            "bibliography = []",
+            r#"bibliography = [{ tag = "knuth1984", title = "Literate Programming", year = 1984 }]"#,
+        );
+        std::fs::write(&conf_path, conf).unwrap();
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "---\nmarp: true\n---\n\n# Intro\n\nSee [](#knuth1984).\n",
+        )
+        .unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide(&slide_dir).unwrap();
+        assert!(check_bibliography(&project, &slide).unwrap().is_some());
+
+        update_bibliography(&project, slide.clone()).unwrap();
+        let updated = std::fs::read_to_string(slide_dir.join("slide.md")).unwrap();
+        assert!(updated.starts_with("---\nmarp: true\n---\n\n# Intro\n"));
+        assert!(updated.ends_with("\n"));
+        assert_eq!(check_bibliography(&project, &slide).unwrap(), None);
+    }
+
+    #[test]
+    fn test_add_bib_entry_from_stub_resolver() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![0; 4096];
+            let len = stream.read(&mut request).unwrap();
+            let body = r#"{"type": "paper-conference", "title": "Attention Is All You Need",
+                "author": [{"given": "Ashish", "family": "Vaswani"}],
+                "issued": {"date-parts": [[2017]]}, "DOI": "10.48550/arXiv.1706.03762"}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request[..len]).to_string()
+        });
+
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+
+        let mut project = Project::get(root.to_path_buf()).unwrap();
+        project.conf.bibliography.resolver = Some(format!("http://{address}/"));
+        let slide = project.get_slide(Path::new("src/intro")).unwrap();
+        let identifier = BibIdentifierArgs {
+            doi: None,
+            arxiv: Some("1706.03762".to_string()),
+            isbn: None,
+        };
+
+        add_bib_entry(&project, &slide, &identifier, None).unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /10.48550/arXiv.1706.03762 HTTP/1.1\r\n"));
+        assert!(request.contains("application/vnd.citationstyles.csl+json"));
+
+        let text = std::fs::read_to_string(root.join("src/intro/slide.toml")).unwrap();
+        let conf: SlideConf = toml::from_str(&text).unwrap();
+        let bibliography = conf.bibliography.unwrap();
+        assert_eq!(bibliography.len(), 1);
+        assert_eq!(bibliography[0].tag, "vaswani2017attention");
+        assert_eq!(bibliography[0].kind.as_deref(), Some("inproceedings"));
+        assert_eq!(bibliography[0].arxiv.as_deref(), Some("1706.03762"));
+        assert_eq!(bibliography[0].doi, None);
+
+        // ISBNs have no default resolver
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide(Path::new("src/intro")).unwrap();
+        let identifier = BibIdentifierArgs {
+            doi: None,
+            arxiv: None,
+            isbn: Some("978-0-201-89683-1".to_string()),
+        };
+        let err = add_bib_entry(&project, &slide, &identifier, None).unwrap_err();
+        assert!(err.to_string().contains("isbn_resolver"));
+    }
 }
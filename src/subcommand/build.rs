@@ -10,14 +10,21 @@ use colored::Colorize;
 use tokio::{process::Command, runtime::Runtime, sync::Semaphore};
 
 use crate::{
-    config::{ImagesConf, PathStrategy, SlideConf},
+    config::{ImagesConf, PathStrategy, SlideConf, TocMode},
+    diagram::{diagram_images, link_diagram_images, render_diagrams},
     images::{
-        optimize_slide_images, prepare_optimized_markdown, relative_path, ImageRewriteMode,
+        optimize_contents_images, prepare_optimized_markdown, relative_path, ImageRewriteMode,
         OptimizeOptions, OptimizeReport,
     },
+    include::{fragment_image_path, fragment_images, link_fragment_images},
     path::{legacy_file_stems, PublishPlan},
     project::Project,
     slide::Slide,
+    subcommand::{
+        bib::{render_bibliography, render_cross_references},
+        index::render_index,
+    },
+    variables::Variables,
 };
 
 /// build commands and their information
@@ -156,9 +163,18 @@ fn prepare_marp_input(
         false,
         ImageRewriteMode::CacheRelativeToMarkdown,
         None,
+        None,
     )
 }
 
+/// expand includes and variables, then render diagrams
+pub(crate) fn expanded_contents(project: &Project, slide: &Slide) -> anyhow::Result<String> {
+    let contents = Variables::new(project, slide).expand(&slide.get_expanded_source()?);
+    render_diagrams(project, slide, &contents)
+}
+
+/// `html_images_dir`: link rendered diagrams and fragment images to this directory, where
+/// HTML builds publish them
 fn prepare_marp_input_with_options(
     project: &Project,
     slide: &Slide,
@@ -166,15 +182,28 @@ fn prepare_marp_input_with_options(
     optimize_images: bool,
     rewrite_mode: ImageRewriteMode,
     temp_dir: Option<&Path>,
+    html_images_dir: Option<&Path>,
 ) -> anyhow::Result<(PathBuf, Option<PathBuf>)> {
     let original_path = slide.dir.join("slide.md");
-    let suffix = project.conf.template.suffix.trim_end();
+    let variables = Variables::new(project, slide);
+    let suffix = variables.expand(project.conf.template.suffix.trim_end());
+    let source = fs::read_to_string(&original_path)?;
+    let mut contents = expanded_contents(project, slide)?;
+    if let Some(images_dir) = html_images_dir {
+        contents = link_diagram_images(project, slide, &contents, images_dir)?;
+        contents = link_fragment_images(project, slide, &contents, images_dir)?;
+    }
+    if project.conf.toc.mode == TocMode::Build {
+        contents = render_index(slide, &contents, &project.conf.toc)?;
+    }
+    contents = render_bibliography(project, slide, &contents)?;
+    contents = render_cross_references(&contents)?;
 
-    if suffix.is_empty() && (!optimize_images || !project.conf.images.enabled) {
+    if suffix.is_empty() && (!optimize_images || !project.conf.images.enabled) && contents == source
+    {
         return Ok((original_path, None));
     }
 
-    let mut contents = fs::read_to_string(&original_path)?;
     if optimize_images {
         contents =
             prepare_optimized_markdown(project, slide, &contents, optimize_options, rewrite_mode)?
@@ -182,7 +211,7 @@ fn prepare_marp_input_with_options(
     }
 
     contents.push_str("\n\n");
-    contents.push_str(suffix);
+    contents.push_str(&suffix);
     contents.push('\n');
 
     let temp_dir = temp_dir.unwrap_or(&slide.dir);
@@ -256,6 +285,7 @@ pub fn build_pdf_commands_with_options<'a>(
         optimize_images,
         ImageRewriteMode::CacheRelativeToMarkdown,
         None,
+        None,
     )?;
     let make_command = |output_stem: String| {
         let mut cmd = Command::new(&project.conf.build.marp_binary);
@@ -333,6 +363,7 @@ pub fn build_pdf_latest_alias_commands_with_options<'a>(
         optimize_images,
         ImageRewriteMode::CacheRelativeToMarkdown,
         None,
+        None,
     )?;
     let make_command = |output_file_name: String| {
         let mut cmd = Command::new(&project.conf.build.marp_binary);
@@ -414,7 +445,7 @@ pub fn build_html_commands_with_options<'a>(
                 project
                     .root_dir
                     .join(&project.conf.output_dir)
-                    .join(&output_stem)
+                    .join(output_stem)
                     .join("index.html"),
             )
             .arg("--title")
@@ -423,7 +454,7 @@ pub fn build_html_commands_with_options<'a>(
             .arg(&project.conf.author)
             .arg("--description")
             .arg(slide.conf.description.clone().unwrap_or_default())
-            .arg(&input_path);
+            .arg(input_path);
 
         cmd
     };
@@ -443,10 +474,10 @@ pub fn build_html_commands_with_options<'a>(
             } else {
                 ImageRewriteMode::CacheRelativeToMarkdown
             };
-            let temp_dir = if optimize_images && project.conf.images.enabled {
-                Some(output_root.as_path())
+            let (temp_dir, html_images_dir) = if optimize_images && project.conf.images.enabled {
+                (Some(output_root.as_path()), None)
             } else {
-                None
+                (None, Some(Path::new("images")))
             };
             let (input_path, temp_input) = prepare_marp_input_with_options(
                 project,
@@ -455,6 +486,7 @@ pub fn build_html_commands_with_options<'a>(
                 optimize_images,
                 rewrite_mode,
                 temp_dir,
+                html_images_dir,
             )?;
 
             Ok(BuildCommand::HTML {
@@ -501,6 +533,7 @@ pub fn build_ogp_image_commands_with_options<'a>(
         optimize_images,
         ImageRewriteMode::CacheRelativeToMarkdown,
         None,
+        None,
     )?;
     let make_command = |output_path: String| {
         let mut cmd = Command::new(&project.conf.build.marp_binary);
@@ -585,10 +618,12 @@ pub fn copy_images_html_with_options(
     slide: &Slide,
     optimize_images: bool,
 ) -> anyhow::Result<()> {
+    let contents = expanded_contents(project, slide)?;
     let report = if optimize_images && project.conf.images.enabled {
-        Some(optimize_slide_images(
+        Some(optimize_contents_images(
             project,
             slide,
+            &contents,
             &OptimizeOptions {
                 dry_run: false,
                 force: false,
@@ -602,6 +637,14 @@ pub fn copy_images_html_with_options(
         copy_optimized_images(project, report)?;
     }
 
+    let (diagrams, fragments) = match report {
+        Some(_) => (vec![], vec![]),
+        None => (
+            diagram_images(project, slide, &contents)?,
+            fragment_images(project, slide, &contents)?,
+        ),
+    };
+
     for stem in PublishPlan::for_slide(project, slide).html_paths {
         let target_images_dir = project
             .root_dir
@@ -619,6 +662,14 @@ pub fn copy_images_html_with_options(
         if report.is_none() {
             std::fs::create_dir_all(&target_images_dir)?;
             copy_images(slide, &target_images_dir)?;
+            for image in &diagrams {
+                std::fs::copy(image, target_images_dir.join(image.file_name().unwrap()))?;
+            }
+            for image in &fragments {
+                let save_path = target_images_dir.join(fragment_image_path(project, image));
+                std::fs::create_dir_all(save_path.parent().unwrap())?;
+                std::fs::copy(image, save_path)?;
+            }
         }
     }
 
@@ -665,7 +716,7 @@ pub fn write_alias_redirects(
                 project,
                 &format!("{}/v{}/ogp.png", plan.canonical_stem, version.conf.version),
             ),
-            &version,
+            version,
         )?;
     }
 
@@ -705,7 +756,7 @@ pub fn write_alias_redirects(
                     project,
                     &format!("{}/v{}/ogp.png", plan.canonical_stem, version.conf.version),
                 ),
-                &version,
+                version,
             )?;
 
             write_redirect(
@@ -719,7 +770,7 @@ pub fn write_alias_redirects(
                     project,
                     &format!("{}/v{}/ogp.png", plan.canonical_stem, version.conf.version),
                 ),
-                &version,
+                version,
             )?;
         }
     }
@@ -752,7 +803,7 @@ fn write_redirect(
 
 fn redirect_html(project: &Project, slide: &Slide, target_url: &str, og_image_url: &str) -> String {
     let title = html_escape(&slide.conf.name);
-    let description = html_escape(&slide.conf.description.clone().unwrap_or_default());
+    let description = html_escape(&redirect_description(slide));
     let target = html_escape(target_url);
     let og_image = html_escape(og_image_url);
     let js_target = js_string_escape(target_url);
@@ -787,6 +838,20 @@ fn redirect_html(project: &Project, slide: &Slide, target_url: &str, og_image_ur
     )
 }
 
+/// description followed by the presentation and notes of the version
+fn redirect_description(slide: &Slide) -> String {
+    slide
+        .conf
+        .description
+        .iter()
+        .filter(|description| !description.is_empty())
+        .cloned()
+        .chain(slide.conf.presentation())
+        .chain(slide.conf.notes.clone())
+        .collect::<Vec<_>>()
+        .join(" — ")
+}
+
 fn absolute_url(project: &Project, path: &str) -> String {
     format!("{}/{}", project.conf.base_url.trim_end_matches('/'), path)
 }
@@ -875,11 +940,14 @@ fn optimized_html_images_dir() -> PathBuf {
 #[cfg(test)]
 mod test_build {
     use super::{
-        build_ogp_image_commands, prepare_marp_input, write_alias_redirects, BuildCommand,
+        build_html_commands_with_options, build_ogp_image_commands, copy_images_html_with_options,
+        prepare_marp_input, write_alias_redirects, BuildCommand,
     };
     use crate::config::{
-        BuildConf, ImagesConf, PathStrategy, ProjectConf, SlideConf, SlideType, TemplateConf,
+        BibEntry, BibliographyConf, BibliographyMode, BuildConf, ImagesConf, PathStrategy,
+        ProjectConf, SlideConf, TemplateConf,
     };
+    use crate::images::OptimizeOptions;
     use crate::project::Project;
     use crate::slide::Slide;
 
@@ -902,8 +970,7 @@ mod test_build {
                     index: String::new(),
                     suffix: String::new(),
                 },
-                build: BuildConf::default(),
-                images: ImagesConf::default(),
+                ..Default::default()
             },
             slides: vec![],
         };
@@ -912,14 +979,7 @@ mod test_build {
             conf: SlideConf {
                 name: "intro".to_string(),
                 version: 1,
-                secret: None,
-                custom_path: None,
-                draft: None,
-                description: None,
-                title_prefix: None,
-                type_: SlideType::Marp,
-                bibliography: None,
-                path_strategy: None,
+                ..Default::default()
             },
         };
 
@@ -948,8 +1008,7 @@ mod test_build {
                     index: String::new(),
                     suffix: "<script src=\"/shared.js\"></script>".to_string(),
                 },
-                build: BuildConf::default(),
-                images: ImagesConf::default(),
+                ..Default::default()
             },
             slides: vec![],
         };
@@ -958,14 +1017,7 @@ mod test_build {
             conf: SlideConf {
                 name: "intro".to_string(),
                 version: 1,
-                secret: None,
-                custom_path: None,
-                draft: None,
-                description: None,
-                title_prefix: None,
-                type_: SlideType::Marp,
-                bibliography: None,
-                path_strategy: None,
+                ..Default::default()
             },
         };
 
@@ -978,6 +1030,104 @@ mod test_build {
         assert!(contents.starts_with("# title\n"));
     }
 
+    #[test]
+    fn prepare_marp_input_expands_variables() {
+        let root = tempfile::tempdir().unwrap();
+        let slide_dir = root.path().join("src").join("intro");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        std::fs::write(slide_dir.join("slide.md"), "# {{ slide.name }}\n").unwrap();
+
+        let project = Project {
+            root_dir: root.path().to_path_buf(),
+            conf: ProjectConf {
+                name: "demo".to_string(),
+                author: "author".to_string(),
+                base_url: "https://example.com".to_string(),
+                output_dir: "output".to_string(),
+                template: TemplateConf {
+                    slide: String::new(),
+                    index: String::new(),
+                    suffix: "v{{ slide.version }} {{ url.pdf }}".to_string(),
+                },
+                ..Default::default()
+            },
+            slides: vec![],
+        };
+        let slide = Slide {
+            dir: slide_dir.clone(),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 2,
+                ..Default::default()
+            },
+        };
+
+        let (input_path, _) = prepare_marp_input(&project, &slide).unwrap();
+        let contents = std::fs::read_to_string(&input_path).unwrap();
+
+        assert!(contents.starts_with("# intro\n"));
+        assert!(contents.ends_with("v2 https://example.com/intro.pdf\n"));
+    }
+
+    #[test]
+    fn prepare_marp_input_numbers_citations_in_build_mode() {
+        let root = tempfile::tempdir().unwrap();
+        let slide_dir = root.path().join("src").join("intro");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        let source = "---\nmarp: true\n---\n\n# Intro\n\nSee [](#knuth84).\n";
+        std::fs::write(slide_dir.join("slide.md"), source).unwrap();
+
+        let project = Project {
+            root_dir: root.path().to_path_buf(),
+            conf: ProjectConf {
+                name: "demo".to_string(),
+                author: "author".to_string(),
+                base_url: "https://example.com".to_string(),
+                output_dir: "output".to_string(),
+                template: TemplateConf {
+                    slide: String::new(),
+                    index: String::new(),
+                    suffix: String::new(),
+                },
+                images: ImagesConf {
+                    enabled: false,
+                    ..ImagesConf::default()
+                },
+                bibliography: BibliographyConf {
+                    mode: BibliographyMode::Build,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            slides: vec![],
+        };
+        let slide = Slide {
+            dir: slide_dir.clone(),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 1,
+                bibliography: Some(vec![BibEntry {
+                    tag: "knuth84".to_string(),
+                    title: "Literate Programming".to_string(),
+                    year: Some(1984),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+        };
+
+        let (input_path, temp_input) = prepare_marp_input(&project, &slide).unwrap();
+        let contents = std::fs::read_to_string(&input_path).unwrap();
+
+        assert!(temp_input.is_some());
+        assert!(contents.contains("See [1](#knuth84:1)."));
+        assert!(contents.contains(r#"<span id="knuth84:1">[1]</span> Literate Programming. 1984"#));
+        assert_eq!(
+            std::fs::read_to_string(slide_dir.join("slide.md")).unwrap(),
+            source
+        );
+    }
+
     #[test]
     fn write_alias_redirects_creates_html_and_pdf_redirects() {
         let root = tempfile::tempdir().unwrap();
@@ -991,13 +1141,12 @@ mod test_build {
                 author: "author".to_string(),
                 base_url: "https://example.com/slides/".to_string(),
                 output_dir: "output".to_string(),
-                template: TemplateConf::default(),
                 build: BuildConf {
                     theme_dir: ".marp/themes".to_string(),
                     marp_binary: "marp".to_string(),
                     path_strategy: PathStrategy::CanonicalWithRedirects,
                 },
-                images: ImagesConf::default(),
+                ..Default::default()
             },
             slides: vec![],
         };
@@ -1008,18 +1157,17 @@ mod test_build {
                 version: 2,
                 secret: Some("uuid".to_string()),
                 custom_path: Some(vec!["talks".to_string()]),
-                draft: None,
                 description: Some("description".to_string()),
-                title_prefix: None,
-                type_: SlideType::Marp,
-                bibliography: None,
-                path_strategy: None,
+                ..Default::default()
             },
         };
         let archived = Slide {
             dir: root.path().join("src/intro/v1"),
             conf: SlideConf {
                 version: 1,
+                presented_at: Some("2026-10-18".to_string()),
+                event: Some("RustConf".to_string()),
+                notes: Some("first talk".to_string()),
                 ..latest.conf.clone()
             },
         };
@@ -1045,7 +1193,11 @@ mod test_build {
         assert!(latest_html.contains(
             r#"<meta name="twitter:image" content="https://example.com/slides/uuid/ogp.png" />"#
         ));
+        assert!(latest_html.contains(r#"<meta name="description" content="description" />"#));
         assert!(v1_html.contains("https://example.com/slides/uuid/v1/"));
+        assert!(v1_html.contains(
+            r#"<meta name="description" content="description — RustConf (2026-10-18) — first talk" />"#
+        ));
         assert!(v1_html.contains(
             r#"<meta property="og:image" content="https://example.com/slides/uuid/v1/ogp.png" />"#
         ));
@@ -1085,9 +1237,7 @@ mod test_build {
                 author: "author".to_string(),
                 base_url: "https://example.com".to_string(),
                 output_dir: "output".to_string(),
-                template: TemplateConf::default(),
-                build: BuildConf::default(),
-                images: ImagesConf::default(),
+                ..Default::default()
             },
             slides: vec![],
         };
@@ -1098,12 +1248,7 @@ mod test_build {
                 version: 2,
                 secret: Some("uuid".to_string()),
                 custom_path: Some(vec!["talks".to_string()]),
-                draft: None,
-                description: None,
-                title_prefix: None,
-                type_: SlideType::Marp,
-                bibliography: None,
-                path_strategy: None,
+                ..Default::default()
             },
         };
 
@@ -1117,4 +1262,181 @@ mod test_build {
             .into_iter()
             .all(|command| matches!(command, BuildCommand::OGPImage { .. })));
     }
+
+    #[test]
+    fn html_build_without_optimization_publishes_diagrams() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tempfile::tempdir().unwrap();
+        let slide_dir = root.path().join("src").join("intro");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "# Graph\n\n```dot\ndigraph { a -> b }\n```\n",
+        )
+        .unwrap();
+
+        // fake renderer: `dot -Tsvg <input> -o <output>`
+        let tool = root.path().join("fake-dot");
+        std::fs::write(&tool, "#!/bin/sh\necho '<svg/>' > \"$4\"\n").unwrap();
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut images = ImagesConf::default();
+        images.dot.tool = tool.to_string_lossy().to_string();
+        images.dot.enabled = true;
+        let project = Project {
+            root_dir: root.path().to_path_buf(),
+            conf: ProjectConf {
+                name: "demo".to_string(),
+                author: "author".to_string(),
+                base_url: "https://example.com".to_string(),
+                output_dir: "output".to_string(),
+                template: TemplateConf {
+                    slide: String::new(),
+                    index: String::new(),
+                    suffix: String::new(),
+                },
+                images,
+                ..Default::default()
+            },
+            slides: vec![],
+        };
+        let slide = Slide {
+            dir: slide_dir.clone(),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 1,
+                ..Default::default()
+            },
+        };
+
+        copy_images_html_with_options(&project, &slide, false).unwrap();
+        let commands = build_html_commands_with_options(
+            &project,
+            &slide,
+            &OptimizeOptions {
+                dry_run: false,
+                force: false,
+            },
+            false,
+        )
+        .unwrap();
+
+        let BuildCommand::HTML {
+            temp_input: Some(temp_input),
+            ..
+        } = &commands[0]
+        else {
+            panic!("expected an HTML build with a temporary input");
+        };
+        let contents = std::fs::read_to_string(temp_input).unwrap();
+        let src = regex::Regex::new(r#"<img src="images/([^"]+)""#)
+            .unwrap()
+            .captures(&contents)
+            .unwrap()[1]
+            .to_string();
+
+        assert!(src.starts_with("diagram-"));
+        assert!(root.path().join("output/intro/images").join(&src).is_file());
+    }
+
+    #[test]
+    fn html_build_without_optimization_publishes_fragment_images() {
+        let root = tempfile::tempdir().unwrap();
+        let slide_dir = root.path().join("src").join("intro");
+        let shared_dir = root.path().join("shared");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        std::fs::create_dir_all(shared_dir.join("images")).unwrap();
+        std::fs::write(shared_dir.join("images/me.png"), "png").unwrap();
+        std::fs::write(shared_dir.join("about.md"), "![me](images/me.png)\n").unwrap();
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "# Intro\n\n<!-- include: ../../shared/about.md -->\n",
+        )
+        .unwrap();
+
+        let project = Project {
+            root_dir: root.path().to_path_buf(),
+            conf: ProjectConf {
+                name: "demo".to_string(),
+                author: "author".to_string(),
+                base_url: "https://example.com".to_string(),
+                output_dir: "output".to_string(),
+                template: TemplateConf {
+                    slide: String::new(),
+                    index: String::new(),
+                    suffix: String::new(),
+                },
+                ..Default::default()
+            },
+            slides: vec![],
+        };
+        let slide = Slide {
+            dir: slide_dir.clone(),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 1,
+                ..Default::default()
+            },
+        };
+
+        copy_images_html_with_options(&project, &slide, false).unwrap();
+        let commands = build_html_commands_with_options(
+            &project,
+            &slide,
+            &OptimizeOptions {
+                dry_run: false,
+                force: false,
+            },
+            false,
+        )
+        .unwrap();
+
+        let BuildCommand::HTML {
+            temp_input: Some(temp_input),
+            ..
+        } = &commands[0]
+        else {
+            panic!("expected an HTML build with a temporary input");
+        };
+        let contents = std::fs::read_to_string(temp_input).unwrap();
+
+        assert!(contents.contains("![me](images/include/shared/images/me.png)"));
+        assert!(root
+            .path()
+            .join("output/intro/images/include/shared/images/me.png")
+            .is_file());
+    }
+
+    #[test]
+    fn prepare_marp_input_resolves_cross_references_after_includes() {
+        let root = tempfile::tempdir().unwrap();
+        let slide_dir = root.path().join("src").join("intro");
+        std::fs::create_dir_all(&slide_dir).unwrap();
+        std::fs::write(slide_dir.join("part.md"), "# A\n\n---\n\n# B\n").unwrap();
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "# Intro\n\nSee [2](#2 \"ref:results\").\n\n---\n\n<!-- include: part.md -->\n\n---\n\n# Results\n\n<!-- label: results -->\n",
+        )
+        .unwrap();
+
+        let project = Project {
+            root_dir: root.path().to_path_buf(),
+            conf: ProjectConf::default(),
+            slides: vec![],
+        };
+        let slide = Slide {
+            dir: slide_dir.clone(),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 1,
+                ..Default::default()
+            },
+        };
+
+        let (input_path, _) = prepare_marp_input(&project, &slide).unwrap();
+        let contents = std::fs::read_to_string(input_path).unwrap();
+
+        assert!(contents.contains("See [4](#4 \"ref:results\")."));
+    }
 }
@@ -0,0 +1,509 @@
+//! compare versions of a slide
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{bail, Context};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+use crate::{
+    contents::{SlideContents, SlidePage},
+    diff::labeled_diff,
+    parser::DiffFormat,
+    project::Project,
+    slide::Slide,
+    subcommand::build::expanded_contents,
+};
+
+/// kind of a change between two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl ChangeKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Modified => "modified",
+        }
+    }
+}
+
+/// change of a page
+#[derive(Debug, PartialEq, Eq)]
+pub struct PageChange {
+    pub kind: ChangeKind,
+    /// page number in the old version
+    pub old_page: Option<usize>,
+    /// page number in the new version
+    pub new_page: Option<usize>,
+    /// title of the page (the first line when untitled)
+    pub title: String,
+    /// unified diff of a modified page
+    pub diff: Option<String>,
+}
+
+/// change of a file in `images/`
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImageChange {
+    pub kind: ChangeKind,
+    /// path relative to the slide directory
+    pub path: String,
+}
+
+/// changes between two versions of a slide
+#[derive(Debug)]
+pub struct VersionDiff {
+    pub name: String,
+    pub from: u8,
+    pub to: u8,
+    pub pages: Vec<PageChange>,
+    pub images: Vec<ImageChange>,
+}
+
+impl VersionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty() && self.images.is_empty()
+    }
+}
+
+/// print the changes between two versions of a slide
+///
+/// `from` defaults to the latest archived version and `to` to the working version.
+pub fn diff_versions(
+    project: &Project,
+    dir: &Path,
+    from: Option<u8>,
+    to: Option<u8>,
+    format: DiffFormat,
+) -> anyhow::Result<()> {
+    let root = project.get_slide_root(dir)?;
+    let diff = version_diff(project, &root, from, to)?;
+
+    match format {
+        DiffFormat::Text => print!("{}", render_text(&diff)),
+        DiffFormat::Markdown => print!("{}", render_markdown(&diff)),
+    }
+
+    Ok(())
+}
+
+/// changes between two versions of the slide rooted at `root`
+pub fn version_diff(
+    project: &Project,
+    root: &Slide,
+    from: Option<u8>,
+    to: Option<u8>,
+) -> anyhow::Result<VersionDiff> {
+    let to = to.unwrap_or(root.conf.version);
+    let from = match from {
+        Some(from) => from,
+        None => project
+            .get_archived_slides(root)?
+            .into_iter()
+            .map(|slide| slide.conf.version)
+            .filter(|&version| version < to)
+            .max()
+            .with_context(|| {
+                format!(
+                    "No archived version of {} before v{to} to compare with",
+                    root.conf.name
+                )
+            })?,
+    };
+
+    let old = slide_version(project, root, from)?;
+    let new = slide_version(project, root, to)?;
+    if !old.conf.type_.is_marp() || !new.conf.type_.is_marp() {
+        bail!("Only Marp slides can be compared: {}", root.conf.name);
+    }
+
+    let title_prefix = new.conf.title_prefix.as_deref().unwrap_or("# ");
+    let pages = compare_pages(
+        &SlideContents::try_from(expanded_contents(project, &old)?.as_str())?,
+        &SlideContents::try_from(expanded_contents(project, &new)?.as_str())?,
+        title_prefix,
+        (from, to),
+    );
+    let images = compare_images(&old.dir, &new.dir)?;
+
+    Ok(VersionDiff {
+        name: root.conf.name.clone(),
+        from,
+        to,
+        pages,
+        images,
+    })
+}
+
+/// the working slide or its archived version
+fn slide_version(project: &Project, root: &Slide, version: u8) -> anyhow::Result<Slide> {
+    if version == root.conf.version {
+        return Ok(root.clone());
+    }
+
+    project.get_archived_slide(root, version)
+}
+
+/// align pages by their titles and report added, removed and modified pages
+fn compare_pages(
+    old: &SlideContents,
+    new: &SlideContents,
+    title_prefix: &str,
+    (from, to): (u8, u8),
+) -> Vec<PageChange> {
+    let key = |page: &SlidePage| {
+        page.title(title_prefix)
+            .map(String::from)
+            .or_else(|| {
+                page.markdown_lines()
+                    .into_iter()
+                    .map(|(_, line)| line.trim())
+                    .find(|line| !line.is_empty())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| "(untitled)".to_string())
+    };
+    let old_keys = old.pages.iter().map(key).collect::<Vec<_>>();
+    let new_keys = new.pages.iter().map(key).collect::<Vec<_>>();
+
+    let removed = |i: usize| PageChange {
+        kind: ChangeKind::Removed,
+        old_page: Some(i + 1),
+        new_page: None,
+        title: old_keys[i].clone(),
+        diff: None,
+    };
+    let added = |j: usize| PageChange {
+        kind: ChangeKind::Added,
+        old_page: None,
+        new_page: Some(j + 1),
+        title: new_keys[j].clone(),
+        diff: None,
+    };
+    let modified = |i: usize, j: usize| {
+        let diff = labeled_diff(
+            &format!("v{from} p. {}", i + 1),
+            &format!("v{to} p. {}", j + 1),
+            &format!("{}\n", old.pages[i].contents()),
+            &format!("{}\n", new.pages[j].contents()),
+        )?;
+        Some(PageChange {
+            kind: ChangeKind::Modified,
+            old_page: Some(i + 1),
+            new_page: Some(j + 1),
+            title: new_keys[j].clone(),
+            diff: Some(diff),
+        })
+    };
+
+    let mut changes = vec![];
+    for op in capture_diff_slices(Algorithm::Myers, &old_keys, &new_keys) {
+        match op {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => changes.extend((0..len).filter_map(|k| modified(old_index + k, new_index + k))),
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => changes.extend((old_index..old_index + old_len).map(removed)),
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => changes.extend((new_index..new_index + new_len).map(added)),
+            // retitled pages are paired in order
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                let paired = old_len.min(new_len);
+                changes.extend((0..paired).filter_map(|k| modified(old_index + k, new_index + k)));
+                changes.extend((old_index + paired..old_index + old_len).map(removed));
+                changes.extend((new_index + paired..new_index + new_len).map(added));
+            }
+        }
+    }
+
+    changes
+}
+
+/// added, removed and modified files in the `images/` directories
+fn compare_images(old_dir: &Path, new_dir: &Path) -> anyhow::Result<Vec<ImageChange>> {
+    let old = image_files(old_dir)?;
+    let new = image_files(new_dir)?;
+
+    let mut changes = vec![];
+    for (path, contents) in &new {
+        let kind = match old.get(path) {
+            None => ChangeKind::Added,
+            Some(old_contents) if old_contents != contents => ChangeKind::Modified,
+            Some(_) => continue,
+        };
+        changes.push(ImageChange {
+            kind,
+            path: path.clone(),
+        });
+    }
+    for path in old.keys().filter(|path| !new.contains_key(*path)) {
+        changes.push(ImageChange {
+            kind: ChangeKind::Removed,
+            path: path.clone(),
+        });
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(changes)
+}
+
+/// files under `images/` by their path relative to the slide directory
+fn image_files(slide_dir: &Path) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![slide_dir.join("images")];
+
+    while let Some(dir) = dirs.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.file_name().is_some_and(|name| name != ".gitignore") {
+                let relative = path.strip_prefix(slide_dir).unwrap_or(&path);
+                files.insert(
+                    relative.to_string_lossy().replace('\\', "/"),
+                    fs::read(&path)?,
+                );
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// summary followed by the diffs of modified pages
+pub fn render_text(diff: &VersionDiff) -> String {
+    let mut text = format!("{}: v{} -> v{}\n", diff.name, diff.from, diff.to);
+    if diff.is_empty() {
+        text.push_str("no changes\n");
+        return text;
+    }
+
+    if !diff.pages.is_empty() {
+        text.push_str("pages:\n");
+        for page in &diff.pages {
+            let pages = match (page.old_page, page.new_page) {
+                (Some(old), Some(new)) if old != new => format!("p. {old} -> p. {new}"),
+                (_, Some(new)) => format!("p. {new}"),
+                (Some(old), None) => format!("p. {old}"),
+                (None, None) => String::new(),
+            };
+            text.push_str(&format!(
+                "  {:<8} {pages:<14} {}\n",
+                page.kind.label(),
+                page.title
+            ));
+        }
+    }
+
+    if !diff.images.is_empty() {
+        text.push_str("images:\n");
+        for image in &diff.images {
+            text.push_str(&format!("  {:<8} {}\n", image.kind.label(), image.path));
+        }
+    }
+
+    for page_diff in diff.pages.iter().filter_map(|page| page.diff.as_ref()) {
+        text.push('\n');
+        text.push_str(page_diff);
+    }
+
+    text
+}
+
+/// changelog for release notes
+pub fn render_markdown(diff: &VersionDiff) -> String {
+    let mut markdown = format!("## {} v{}\n\n", diff.name, diff.to);
+    if diff.is_empty() {
+        markdown.push_str(&format!("No changes since v{}.\n", diff.from));
+        return markdown;
+    }
+    markdown.push_str(&format!("Changes since v{}.\n", diff.from));
+
+    if !diff.pages.is_empty() {
+        markdown.push_str("\n### Pages\n\n");
+        for page in &diff.pages {
+            let item = match page.kind {
+                ChangeKind::Added => format!("Added p. {}", page.new_page.unwrap_or_default()),
+                ChangeKind::Modified => {
+                    format!("Updated p. {}", page.new_page.unwrap_or_default())
+                }
+                ChangeKind::Removed => format!(
+                    "Removed p. {} of v{}",
+                    page.old_page.unwrap_or_default(),
+                    diff.from
+                ),
+            };
+            markdown.push_str(&format!("- {item}: {}\n", page.title));
+        }
+    }
+
+    if !diff.images.is_empty() {
+        markdown.push_str("\n### Images\n\n");
+        for image in &diff.images {
+            let kind = match image.kind {
+                ChangeKind::Added => "Added",
+                ChangeKind::Modified => "Updated",
+                ChangeKind::Removed => "Removed",
+            };
+            markdown.push_str(&format!("- {kind} `{}`\n", image.path));
+        }
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        config::SlideType,
+        contents::SlideContents,
+        project::Project,
+        subcommand::{add::add, init::init, version::bump},
+    };
+
+    use super::{compare_pages, render_markdown, version_diff, ChangeKind};
+
+    #[test]
+    fn test_compare_pages_aligns_by_title() {
+        let old = SlideContents::try_from(
+            "---\nmarp: true\n---\n# Intro\n\n---\n\n# Method\n\nold\n\n---\n\n# Legacy\n\n---\n\n# End\n",
+        )
+        .unwrap();
+        let new = SlideContents::try_from(
+            "---\nmarp: true\n---\n# Intro\n\n---\n\n# Motivation\n\n---\n\n# Method\n\nnew\n\n---\n\n# End\n",
+        )
+        .unwrap();
+
+        let changes = compare_pages(&old, &new, "# ", (1, 2));
+
+        let summary = changes
+            .iter()
+            .map(|c| (c.kind, c.old_page, c.new_page, c.title.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Added, None, Some(2), "Motivation"),
+                (ChangeKind::Modified, Some(2), Some(3), "Method"),
+                (ChangeKind::Removed, Some(3), None, "Legacy"),
+            ]
+        );
+        assert_eq!(
+            changes[1].diff.as_deref(),
+            Some("--- v1 p. 2\n+++ v2 p. 3\n@@ -1,3 +1,3 @@\n # Method\n \n-old\n+new\n")
+        );
+    }
+
+    #[test]
+    fn test_version_diff_with_images() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+
+        let slide_dir = root.join("src/intro");
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "---\nmarp: true\n---\n# Intro\n",
+        )
+        .unwrap();
+        std::fs::write(slide_dir.join("images/old.png"), "old").unwrap();
+        std::fs::write(slide_dir.join("images/fig.png"), "v1").unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        bump(&project, slide_dir.clone(), Default::default()).unwrap();
+
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "---\nmarp: true\n---\n# Intro\n\n---\n\n# Results\n",
+        )
+        .unwrap();
+        std::fs::write(slide_dir.join("images/fig.png"), "v2").unwrap();
+        std::fs::write(slide_dir.join("images/new.png"), "new").unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide_root(Path::new("src/intro")).unwrap();
+        let diff = version_diff(&project, &slide, None, None).unwrap();
+
+        assert_eq!((diff.from, diff.to), (1, 2));
+        assert_eq!(
+            render_markdown(&diff),
+            "## intro v2\n\nChanges since v1.\n\n### Pages\n\n- Added p. 2: Results\n\n### Images\n\n- Updated `images/fig.png`\n- Added `images/new.png`\n- Removed `images/old.png`\n"
+        );
+
+        assert!(version_diff(&project, &slide, Some(3), None)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Version v3 of intro does not exist"));
+    }
+
+    #[test]
+    fn test_version_diff_follows_includes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+
+        let slide_dir = root.join("src/intro");
+        std::fs::create_dir_all(slide_dir.join("parts")).unwrap();
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "---\nmarp: true\n---\n<!-- include: parts/intro.md -->\n\n---\n\n# Method\n\nold\n",
+        )
+        .unwrap();
+        std::fs::write(
+            slide_dir.join("parts/intro.md"),
+            "# Intro\n\n---\n\n# Background\n\nold\n",
+        )
+        .unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        bump(&project, slide_dir.clone(), Default::default()).unwrap();
+
+        std::fs::write(
+            slide_dir.join("slide.md"),
+            "---\nmarp: true\n---\n<!-- include: parts/intro.md -->\n\n---\n\n# Method\n\nnew\n",
+        )
+        .unwrap();
+        std::fs::write(
+            slide_dir.join("parts/intro.md"),
+            "# Intro\n\n---\n\n# Background\n\nnew\n",
+        )
+        .unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide_root(Path::new("src/intro")).unwrap();
+        let diff = version_diff(&project, &slide, None, None).unwrap();
+
+        let summary = diff
+            .pages
+            .iter()
+            .map(|c| (c.kind, c.old_page, c.new_page, c.title.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Modified, Some(2), Some(2), "Background"),
+                (ChangeKind::Modified, Some(3), Some(3), "Method"),
+            ]
+        );
+    }
+}
@@ -3,98 +3,676 @@
 use std::fs;
 
 use anyhow::bail;
+use itertools::Itertools;
 use regex::Regex;
 
-use crate::slide::Slide;
+use crate::{
+    config::{TocConf, TocNumbering},
+    contents::{Fences, SlideContents, SlidePage},
+    diff::unified_diff,
+    slide::Slide,
+};
 
-/// put index to slide and return the table of contents
-pub fn put_index(slide: &Slide) -> anyhow::Result<String> {
+/// marker of generated agenda pages
+const AGENDA_MARKER: &str = "<!-- agenda -->";
+
+/// put index to slide, fill its table of contents regions and return the table of contents
+pub fn put_index(slide: &Slide, conf: &TocConf) -> anyhow::Result<String> {
+    // path to slide
+    let slide_path = slide.slide_path();
+
+    let (_, indexed, toc) = index_slide(slide, conf)?;
+
+    // write for file
+    let Ok(_) = fs::write(&slide_path, indexed) else {
+        bail!(
+            "Failed to write the slide file: {}",
+            slide_path.to_string_lossy()
+        );
+    };
+
+    // output toc
+    Ok(toc)
+}
+
+/// unified diff of the changes `put_index` would make, without writing
+pub fn check_index(slide: &Slide, conf: &TocConf) -> anyhow::Result<Option<String>> {
+    let (contents, indexed, _) = index_slide(slide, conf)?;
+
+    Ok(unified_diff(&slide.slide_path(), &contents, &indexed))
+}
+
+/// current and indexed contents of the slide file, and the table of contents
+fn index_slide(slide: &Slide, conf: &TocConf) -> anyhow::Result<(String, String, String)> {
     // path to slide
     let slide_path = slide.slide_path();
 
-    // read lines of slide file
-    let Ok(mut lines) =
-        fs::read_to_string(&slide_path).map(|s| s.lines().map(String::from).collect::<Vec<_>>())
-    else {
+    // read slide file
+    let Ok(contents) = fs::read_to_string(&slide_path) else {
         bail!(
             "The slide file does not exist: {}",
             slide_path.to_string_lossy()
         );
     };
 
-    // prefix of slide title
-    let title_prefix: &str = slide.conf.title_prefix.as_deref().unwrap_or("# ");
+    let (indexed, toc) = index_titles(&contents, title_prefix(slide), section_prefix(slide), conf)?;
 
-    // regex for slide number
-    let slide_number = Regex::new(r"\(\d+/\d+\)$").unwrap();
+    Ok((contents, indexed, toc))
+}
 
-    // get slide titles
-    let mut toc = String::new();
+/// number titles of the build input and fill its table of contents regions
+///
+/// `slide.md` is left as it is.
+pub fn render_index(slide: &Slide, contents: &str, conf: &TocConf) -> anyhow::Result<String> {
+    let (indexed, _) = index_titles(contents, title_prefix(slide), section_prefix(slide), conf)?;
 
-    let mut titles = lines
-        .iter()
-        .enumerate()
-        .filter(|&(_, line)| line.starts_with(title_prefix))
-        .map(|(i, line)| {
-            let title = slide_number.replace(line, "").trim().to_string();
-            (i, title)
-        })
-        // run length encoding
-        .fold(vec![], |mut acc: Vec<(Vec<usize>, String)>, (i, title)| {
-            if let Some((idxs, last_title)) = acc.last_mut() {
-                if title == *last_title {
-                    idxs.push(i);
-                } else {
-                    acc.push((vec![i], title));
+    Ok(indexed)
+}
+
+/// prefix of slide title
+fn title_prefix(slide: &Slide) -> &str {
+    slide.conf.title_prefix.as_deref().unwrap_or("# ")
+}
+
+/// prefix of section title
+fn section_prefix(slide: &Slide) -> Option<&str> {
+    slide.conf.section_prefix.as_deref()
+}
+
+/// heading in the Markdown lines of a page
+#[derive(Debug)]
+pub(crate) struct Heading {
+    /// index of the page
+    pub page: usize,
+    /// index of the line in the page
+    pub line: usize,
+    pub kind: HeadingKind,
+    /// text without the prefix
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HeadingKind {
+    /// line starting with `section_prefix`
+    Section,
+    /// line starting with `title_prefix`
+    Title,
+    /// `#` heading below the title, at the level relative to the title (2, 3, …)
+    Subheading(usize),
+}
+
+/// append `(i/n)` to repeated titles, fill the table of contents regions and return the new
+/// contents and the table of contents
+///
+/// Fenced code and HTML comments are skipped. With `section_prefix`, titles are numbered
+/// within each section, and with `conf.agenda` an agenda page is (re)generated before each
+/// section.
+pub fn index_titles(
+    contents: &str,
+    title_prefix: &str,
+    section_prefix: Option<&str>,
+    conf: &TocConf,
+) -> anyhow::Result<(String, String)> {
+    let mut slide = SlideContents::parse_without_frontmatter_check(contents)?;
+
+    // drop generated agenda pages
+    slide.pages.retain(|page| !is_agenda_page(page));
+
+    let mut headings = headings(&slide, title_prefix, section_prefix, conf);
+    number_titles(&mut headings);
+
+    if conf.appendix_numbering {
+        number_appendix(&mut slide, title_prefix);
+    }
+
+    // put slide number
+    for (page_index, page) in slide.pages.iter_mut().enumerate() {
+        let titles = headings
+            .iter()
+            .filter(|h| h.page == page_index && h.kind == HeadingKind::Title)
+            .collect::<Vec<_>>();
+        if titles.is_empty() {
+            continue;
+        }
+
+        let mut lines = page
+            .contents()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        for title in titles {
+            lines[title.line] = format!("{title_prefix}{}", title.text);
+        }
+        page.set_contents(&lines.join("\n"));
+    }
+
+    let toc = make_toc(&headings, conf);
+
+    if conf.agenda {
+        insert_agenda_pages(&mut slide, &headings, title_prefix, conf);
+    }
+
+    for page in slide.pages.iter_mut() {
+        let filled = fill_toc(page.contents(), &toc);
+        page.set_contents(&filled);
+    }
+
+    Ok((slide.to_marp(), toc))
+}
+
+/// headings of the slide with its title and section prefixes, as `toc` finds them
+pub(crate) fn slide_headings(
+    slide: &Slide,
+    contents: &SlideContents,
+    conf: &TocConf,
+) -> Vec<Heading> {
+    headings(contents, title_prefix(slide), section_prefix(slide), conf)
+}
+
+/// whether the page is an agenda page generated by `toc`
+fn is_agenda_page(page: &SlidePage) -> bool {
+    page.contents().starts_with(AGENDA_MARKER)
+}
+
+/// section titles, slide titles and subheadings of the pages before the appendix
+///
+/// Generated agenda pages and references pages, which `bib` may append after indexing,
+/// are skipped.
+fn headings(
+    slide: &SlideContents,
+    title_prefix: &str,
+    section_prefix: Option<&str>,
+    conf: &TocConf,
+) -> Vec<Heading> {
+    let heading = Regex::new(r"^(#+)\s+(.*)$").unwrap();
+
+    // subheadings are listed only for `#` title prefixes
+    let prefix = title_prefix.trim_end();
+    let base_level =
+        (!prefix.is_empty() && prefix.chars().all(|c| c == '#')).then_some(prefix.len());
+
+    let mut headings = vec![];
+    for (page_index, page) in slide.main_pages().iter().enumerate() {
+        if page.has_references_marker() || is_agenda_page(page) {
+            continue;
+        }
+        let mut has_title = false;
+
+        for (line_index, line) in page.markdown_lines() {
+            let section = section_prefix
+                .filter(|_| !line.starts_with(title_prefix))
+                .and_then(|prefix| line.strip_prefix(prefix));
+
+            let (kind, text) = if let Some(text) = section {
+                (HeadingKind::Section, text)
+            } else if let Some(text) = line.strip_prefix(title_prefix) {
+                if has_title && conf.first_heading_only {
+                    continue;
+                }
+                has_title = true;
+                (HeadingKind::Title, text)
+            } else if let Some((base, caps)) = base_level.zip(heading.captures(line)) {
+                let hashes = caps[1].len();
+                if hashes <= base {
+                    continue;
                 }
+                (
+                    HeadingKind::Subheading(hashes - base + 1),
+                    caps.get(2).unwrap().as_str(),
+                )
             } else {
-                acc.push((vec![i], title));
+                continue;
+            };
+
+            headings.push(Heading {
+                page: page_index,
+                line: line_index,
+                kind,
+                text: text.trim().to_string(),
+            });
+        }
+    }
+
+    headings
+}
+
+/// append `(i/n)` to titles repeated in a row within a section
+fn number_titles(headings: &mut [Heading]) {
+    // regex for slide number
+    let slide_number = Regex::new(r"\s*\(\d+/\d+\)$").unwrap();
+
+    // run length encoding of (section, title)
+    let mut section = 0;
+    let mut runs: Vec<(Vec<usize>, (usize, String))> = vec![];
+    for (i, heading) in headings.iter().enumerate() {
+        match heading.kind {
+            HeadingKind::Section => section += 1,
+            HeadingKind::Title => {
+                let title = (section, slide_number.replace(&heading.text, "").to_string());
+                match runs.last_mut() {
+                    Some((idxs, last_title)) if *last_title == title => idxs.push(i),
+                    _ => runs.push((vec![i], title)),
+                }
             }
-            acc
-        })
-        .into_iter()
-        // make pair of slide number and title
-        .flat_map(|(idxs, title)| {
-            // add to table of contents
-            toc.push_str(&format!(
-                "1. {}\n",
-                title.trim_start_matches(title_prefix).trim()
-            ));
-
-            let n = idxs.len();
-            if n > 1 {
-                let itr = idxs
-                    .into_iter()
-                    .enumerate()
-                    .map(move |(i, l)| (l, format!("{} ({}/{})", title, i + 1, n)));
-
-                either::Left(itr)
+            HeadingKind::Subheading(_) => {}
+        }
+    }
+
+    for (idxs, (_, title)) in runs {
+        let n = idxs.len();
+        for (k, i) in idxs.into_iter().enumerate() {
+            headings[i].text = if n > 1 {
+                format!("{title} ({}/{n})", k + 1)
             } else {
-                let itr = idxs.into_iter().map(move |l| (l, title.clone()));
+                title.clone()
+            };
+        }
+    }
+}
 
-                either::Right(itr)
+/// prefix the first title of each page after the appendix separator with `A1.`, `A2.`, …
+fn number_appendix(slide: &mut SlideContents, title_prefix: &str) {
+    let appendix_number = Regex::new(r"^A\d+\.\s+").unwrap();
+
+    let Some(start) = slide.appendix_start() else {
+        return;
+    };
+
+    let mut number = 0;
+    for page in slide.pages[start + 1..].iter_mut() {
+        let Some((line_index, text)) = page
+            .markdown_lines()
+            .into_iter()
+            .find_map(|(i, line)| Some((i, line.strip_prefix(title_prefix)?.trim())))
+        else {
+            continue;
+        };
+
+        number += 1;
+        let title = format!(
+            "{title_prefix}A{number}. {}",
+            appendix_number.replace(text, "")
+        );
+
+        let mut lines = page
+            .contents()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        lines[line_index] = title;
+        page.set_contents(&lines.join("\n"));
+    }
+}
+
+/// table of contents of the sections, the numbered titles and their subheadings up to
+/// `conf.depth`
+///
+/// Titles after a section title are nested below it.
+fn make_toc(headings: &[Heading], conf: &TocConf) -> String {
+    let slide_number = Regex::new(r"\s*\(\d+/\d+\)$").unwrap();
+
+    let depth = conf.depth.max(1);
+    let mut counters = vec![0; depth + 1];
+    let mut in_section = false;
+    let mut last_title = None;
+    let mut toc = String::new();
+
+    for heading in headings {
+        let level = match heading.kind {
+            HeadingKind::Section => {
+                in_section = true;
+                last_title = None;
+                counters[0] += 1;
+                counters[1..].fill(0);
+                toc.push_str(&list_item(conf.numbering, &counters[..1], &heading.text));
+                toc.push('\n');
+                continue;
             }
-        })
-        .peekable();
+            HeadingKind::Title => 1,
+            HeadingKind::Subheading(level) => level,
+        };
+        if level > depth {
+            continue;
+        }
 
-    // put slide number
-    lines.iter_mut().enumerate().for_each(|(i, line)| {
-        if titles.peek().is_some_and(|(l, _)| *l == i) {
-            let (_, title) = titles.next().unwrap();
-            *line = title;
+        let title = slide_number.replace(&heading.text, "").to_string();
+        if level == 1 {
+            // continuation pages share the title
+            if !conf.continuations && last_title.as_ref() == Some(&title) {
+                continue;
+            }
+            last_title = Some(title.clone());
         }
-    });
+        let title = if level == 1 && conf.continuations {
+            heading.text.clone()
+        } else {
+            title
+        };
 
-    lines.push(String::new());
+        // titles in a section are one level deeper
+        let level = level + usize::from(in_section);
+        counters[level - 1] += 1;
+        counters[level..].fill(0);
 
-    // write for file
-    let Ok(_) = fs::write(&slide_path, lines.join("\n")) else {
-        bail!(
-            "Failed to write the slide file: {}",
-            slide_path.to_string_lossy()
+        toc.push_str(&list_item(conf.numbering, &counters[..level], &title));
+        toc.push('\n');
+    }
+
+    toc
+}
+
+/// list item at the depth of `counters`
+fn list_item(numbering: TocNumbering, counters: &[usize], title: &str) -> String {
+    let level = counters.len();
+    match numbering {
+        TocNumbering::Ordered => format!("{}1. {title}", "   ".repeat(level - 1)),
+        TocNumbering::Bullet => format!("{}- {title}", "  ".repeat(level - 1)),
+        TocNumbering::Decimal => format!(
+            "{}{}. {title}",
+            "   ".repeat(level - 1),
+            counters.iter().join(".")
+        ),
+    }
+}
+
+/// insert an agenda page highlighting the current section before each section page
+fn insert_agenda_pages(
+    slide: &mut SlideContents,
+    headings: &[Heading],
+    title_prefix: &str,
+    conf: &TocConf,
+) {
+    let sections = headings
+        .iter()
+        .filter(|h| h.kind == HeadingKind::Section)
+        .collect::<Vec<_>>();
+
+    for (current, section) in sections.iter().enumerate().rev() {
+        let items = sections
+            .iter()
+            .enumerate()
+            .map(|(n, other)| {
+                let title = if n == current {
+                    format!("**{}**", other.text)
+                } else {
+                    other.text.clone()
+                };
+                list_item(conf.numbering, &[n + 1], &title)
+            })
+            .join("\n");
+
+        let agenda = format!(
+            "{AGENDA_MARKER}\n\n{title_prefix}{}\n\n{items}",
+            conf.agenda_title()
         );
+        slide.pages.insert(section.page, SlidePage::new(&agenda));
+    }
+}
+
+/// replace `<!-- toc -->` … `<!-- /toc -->` regions of the page with the table of contents
+///
+/// A `<!-- toc -->` marker without the closing marker gets a new region.
+fn fill_toc(page: &str, toc: &str) -> String {
+    let open = Regex::new(r"^\s*<!--\s*toc\s*-->\s*$").unwrap();
+    let close = Regex::new(r"^\s*<!--\s*/toc\s*-->\s*$").unwrap();
+
+    let lines = page.lines().collect::<Vec<_>>();
+    let mut filled = vec![];
+    let mut fences = Fences::default();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        fences.toggles(line);
+        filled.push(line.to_string());
+        i += 1;
+
+        if fences.is_open() || !open.is_match(line) {
+            continue;
+        }
+
+        // skip the old region
+        let region_end = lines[i..]
+            .iter()
+            .take_while(|line| !open.is_match(line))
+            .position(|line| close.is_match(line));
+        if let Some(end) = region_end {
+            i += end + 1;
+        }
+
+        filled.extend(toc.lines().map(String::from));
+        filled.push("<!-- /toc -->".to_string());
+    }
+
+    filled.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::{
+        config::{BibEntry, BibliographyConf, SlideConf, TocConf, TocNumbering},
+        contents::SlideContents,
+        slide::Slide,
     };
 
-    // output toc
-    Ok(toc)
+    use super::{check_index, index_titles, put_index, render_index};
+
+    fn slide(dir: &Path) -> Slide {
+        Slide {
+            dir: dir.to_path_buf(),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 1,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_render_index_fills_toc_marker() {
+        let slide = slide(&PathBuf::from("src/intro"));
+        let source = "---\nmarp: true\n---\n\n# Agenda\n\n<!-- toc -->\n\n---\n\n# Method (1/3)\n\n---\n\n# Method\n\n```md\n<!-- toc -->\n```\n\n---\n\n# Results\n";
+
+        let rendered = render_index(&slide, source, &TocConf::default()).unwrap();
+
+        assert_eq!(
+            rendered,
+            "---\nmarp: true\n---\n\n# Agenda\n\n<!-- toc -->\n1. Agenda\n1. Method\n1. Results\n<!-- /toc -->\n\n---\n\n# Method (1/2)\n\n---\n\n# Method (2/2)\n\n```md\n<!-- toc -->\n```\n\n---\n\n# Results\n"
+        );
+
+        // the region is replaced when rendered again
+        assert_eq!(
+            render_index(&slide, &rendered, &TocConf::default()).unwrap(),
+            rendered
+        );
+    }
+
+    #[test]
+    fn test_index_titles_skips_code_and_comments() {
+        let source = "---\nmarp: true\n---\n\n# Setup\n\n```bash\n# install\nnpm i\n```\n\n---\n\n# Setup\n\n<!--\n# draft title\n-->\n\n```yaml\n---\n# config\n```\n";
+
+        let (indexed, toc) = index_titles(source, "# ", None, &TocConf::default()).unwrap();
+
+        assert_eq!(toc, "1. Setup\n");
+        assert_eq!(
+            indexed,
+            "---\nmarp: true\n---\n\n# Setup (1/2)\n\n```bash\n# install\nnpm i\n```\n\n---\n\n# Setup (2/2)\n\n<!--\n# draft title\n-->\n\n```yaml\n---\n# config\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_index_titles_with_first_heading_only() {
+        let source = "---\nmarp: true\n---\n\n# Results\n\n# Accuracy\n\n---\n\n# Results\n";
+
+        let (indexed, toc) = index_titles(source, "# ", None, &TocConf::default()).unwrap();
+        assert_eq!(toc, "1. Results\n1. Accuracy\n1. Results\n");
+        assert!(indexed.contains("# Results\n\n# Accuracy\n\n---\n\n# Results"));
+
+        let conf = TocConf {
+            first_heading_only: true,
+            ..Default::default()
+        };
+        let (indexed, toc) = index_titles(source, "# ", None, &conf).unwrap();
+        assert_eq!(toc, "1. Results\n");
+        assert!(indexed.contains("# Results (1/2)\n\n# Accuracy\n\n---\n\n# Results (2/2)"));
+    }
+
+    #[test]
+    fn test_index_titles_excludes_appendix() {
+        let source = "---\nmarp: true\n---\n\n# Results\n\n<!-- toc -->\n\n---\n\n<!-- appendix -->\n\n# Appendix\n\n---\n\n# Results\n\n---\n\n# A1. Proofs\n\n---\n\n# Proofs\n";
+
+        let (indexed, toc) = index_titles(source, "# ", None, &TocConf::default()).unwrap();
+        assert_eq!(toc, "1. Results\n");
+        assert!(indexed.ends_with("# Results\n\n---\n\n# A1. Proofs\n\n---\n\n# Proofs\n"));
+
+        let conf = TocConf {
+            appendix_numbering: true,
+            ..Default::default()
+        };
+        let (indexed, _) = index_titles(source, "# ", None, &conf).unwrap();
+        assert!(indexed.ends_with(
+            "# Appendix\n\n---\n\n# A1. Results\n\n---\n\n# A2. Proofs\n\n---\n\n# A3. Proofs\n"
+        ));
+        assert_eq!(
+            index_titles(&indexed, "# ", None, &conf).unwrap().0,
+            indexed
+        );
+    }
+
+    #[test]
+    fn test_check_index_does_not_write() {
+        let tmp = tempfile::tempdir().unwrap();
+        let slide = slide(tmp.path());
+        let source = "---\nmarp: true\n---\n# Method\n\n---\n\n# Method";
+        std::fs::write(slide.slide_path(), source).unwrap();
+
+        let diff = check_index(&slide, &TocConf::default()).unwrap().unwrap();
+        assert!(diff.contains("-# Method\n+# Method (1/2)\n"));
+        assert_eq!(std::fs::read_to_string(slide.slide_path()).unwrap(), source);
+
+        put_index(&slide, &TocConf::default()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(slide.slide_path()).unwrap(),
+            "---\nmarp: true\n---\n# Method (1/2)\n\n---\n\n# Method (2/2)"
+        );
+        assert_eq!(check_index(&slide, &TocConf::default()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_index_titles_with_toc_options() {
+        let source = "---\nmarp: true\n---\n\n# Intro\n\n---\n\n# Method\n\n## Setup\n\n### Details\n\n---\n\n# Method\n\n## Metrics\n";
+
+        let conf = TocConf {
+            depth: 2,
+            numbering: TocNumbering::Decimal,
+            ..Default::default()
+        };
+        assert_eq!(
+            index_titles(source, "# ", None, &conf).unwrap().1,
+            "1. Intro\n2. Method\n   2.1. Setup\n   2.2. Metrics\n"
+        );
+
+        let conf = TocConf {
+            numbering: TocNumbering::Bullet,
+            continuations: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            index_titles(source, "# ", None, &conf).unwrap().1,
+            "- Intro\n- Method (1/2)\n- Method (2/2)\n"
+        );
+    }
+
+    #[test]
+    fn test_index_titles_with_sections_and_agenda() {
+        let source = "---\nmarp: true\n---\n\n# Basics\n\n## Terms\n\n---\n\n## Terms\n\n---\n\n# Advanced\n\n---\n\n## Terms\n";
+
+        let conf = TocConf {
+            agenda: true,
+            ..Default::default()
+        };
+        let (indexed, toc) = index_titles(source, "## ", Some("# "), &conf).unwrap();
+
+        assert_eq!(toc, "1. Basics\n   1. Terms\n1. Advanced\n   1. Terms\n");
+        assert_eq!(
+            indexed,
+            "---\nmarp: true\n---\n\n<!-- agenda -->\n\n## Agenda\n\n1. **Basics**\n1. Advanced\n\n---\n\n# Basics\n\n## Terms (1/2)\n\n---\n\n## Terms (2/2)\n\n---\n\n<!-- agenda -->\n\n## Agenda\n\n1. Basics\n1. **Advanced**\n\n---\n\n# Advanced\n\n---\n\n## Terms\n"
+        );
+
+        // agenda pages are regenerated, and removed when disabled
+        assert_eq!(
+            index_titles(&indexed, "## ", Some("# "), &conf).unwrap().0,
+            indexed
+        );
+        assert_eq!(
+            index_titles(&indexed, "## ", Some("# "), &TocConf::default())
+                .unwrap()
+                .0,
+            "---\nmarp: true\n---\n\n# Basics\n\n## Terms (1/2)\n\n---\n\n## Terms (2/2)\n\n---\n\n# Advanced\n\n---\n\n## Terms\n"
+        );
+
+        let conf = TocConf {
+            numbering: TocNumbering::Decimal,
+            ..Default::default()
+        };
+        assert_eq!(
+            index_titles(source, "## ", Some("# "), &conf).unwrap().1,
+            "1. Basics\n   1.1. Terms\n2. Advanced\n   2.1. Terms\n"
+        );
+    }
+
+    #[test]
+    fn test_index_titles_keeps_layout() {
+        let source = "# Intro\n\n<!-- toc -->\n1. Intro\n1. Method (1/2)\n1. Method (2/2)\n<!-- /toc -->\n\n---\n\n# Method (1/2)\n\n---\n\n# Method (2/2)\n";
+
+        let (indexed, _) = index_titles(source, "# ", None, &TocConf::default()).unwrap();
+        assert_eq!(
+            indexed,
+            source.replace("1. Method (1/2)\n1. Method (2/2)", "1. Method")
+        );
+
+        let (again, _) = index_titles(&indexed, "# ", None, &TocConf::default()).unwrap();
+        assert_eq!(again, indexed);
+    }
+
+    #[test]
+    fn test_toc_region_stays_in_its_page() {
+        let source = "---\nmarp: true\n---\n<!-- toc -->\n\n---\n\n# Next\n<!-- /toc -->\n";
+
+        assert_eq!(
+            index_titles(source, "# ", None, &TocConf::default())
+                .unwrap()
+                .0,
+            "---\nmarp: true\n---\n<!-- toc -->\n1. Next\n<!-- /toc -->\n\n---\n\n# Next\n<!-- /toc -->\n"
+        );
+    }
+
+    #[test]
+    fn test_index_titles_skips_references_pages() {
+        let source = "---\nmarp: true\n---\n\n# Intro\n\n<!-- toc -->\n\n---\n\n# Method\n\nSee [](#a) and [](#b).\n";
+        let entries = ["a", "b"].map(|tag| BibEntry {
+            tag: tag.to_string(),
+            title: tag.to_uppercase(),
+            ..Default::default()
+        });
+        let conf = BibliographyConf {
+            references_page: true,
+            references_per_page: Some(1),
+            ..Default::default()
+        };
+
+        // toc, then bib appending the references pages
+        let (indexed, toc) = index_titles(source, "# ", None, &TocConf::default()).unwrap();
+        assert_eq!(toc, "1. Intro\n1. Method\n");
+        let mut contents = SlideContents::try_from(indexed.as_str()).unwrap();
+        contents.modify_bibliography_with_style(&entries, &conf);
+        let cited = contents.to_marp();
+        assert!(cited.contains("# References (cont.)"));
+
+        let (again, toc) = index_titles(&cited, "# ", None, &TocConf::default()).unwrap();
+        assert_eq!(toc, "1. Intro\n1. Method\n");
+        assert_eq!(again, cited);
+    }
 }
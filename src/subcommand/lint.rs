@@ -0,0 +1,562 @@
+//! static checks for slides
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::bail;
+use colored::Colorize;
+use itertools::Itertools;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    contents::SlideContents,
+    images::{missing_image_refs, scan_image_usages},
+    parser::ReportFormat,
+    path::{alias_stems, canonical_stem},
+    project::Project,
+    slide::Slide,
+    subcommand::index::{slide_headings, HeadingKind},
+    template::PublishedSlide,
+};
+
+/// themes bundled with Marp
+const BUILTIN_THEMES: [&str; 3] = ["default", "gaia", "uncover"];
+
+/// Marp image keywords which are not alternative text
+const MARP_IMAGE_KEYWORDS: [&str; 10] = [
+    "left",
+    "right",
+    "contain",
+    "cover",
+    "fit",
+    "auto",
+    "vertical",
+    "blur",
+    "sepia",
+    "grayscale",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// problem found by lint
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    /// slide directory relative to the project root
+    pub slide: String,
+    /// severity of the problem
+    pub severity: Severity,
+    /// rule name
+    pub rule: &'static str,
+    /// page number (1-origin)
+    pub page: Option<usize>,
+    /// human readable message
+    pub message: String,
+}
+
+/// lint slides and fail when errors are found
+pub fn lint(project: &Project, slides: &[Slide], format: ReportFormat) -> anyhow::Result<()> {
+    let issues = collect_issues(project, slides);
+    println!("{}", render(&issues, format)?);
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        bail!("lint failed with {errors} error(s)");
+    }
+
+    Ok(())
+}
+
+/// collect problems of slides
+///
+/// Unreadable or invalid slides are reported as issues instead of failing.
+pub fn collect_issues(project: &Project, slides: &[Slide]) -> Vec<LintIssue> {
+    let themes = known_themes(project);
+    let mut issues = vec![];
+
+    for slide in slides {
+        let mut report = |severity, rule, page, message| {
+            issues.push(LintIssue {
+                slide: display_path(project, &slide.dir),
+                severity,
+                rule,
+                page,
+                message,
+            })
+        };
+
+        lint_config(project, slide, &mut report);
+
+        if slide.conf.type_.is_marp() {
+            match slide.get_expanded_source() {
+                Ok(source) => match SlideContents::try_from(source.as_str()) {
+                    Ok(contents) => lint_contents(project, slide, &contents, &themes, &mut report),
+                    Err(e) => report(Severity::Error, "invalid-contents", None, e.to_string()),
+                },
+                Err(e) => report(Severity::Error, "invalid-include", None, format!("{e:#}")),
+            }
+        }
+    }
+
+    issues
+}
+
+/// render issues as text or JSON
+pub fn render(issues: &[LintIssue], format: ReportFormat) -> anyhow::Result<String> {
+    if format == ReportFormat::Json {
+        return Ok(serde_json::to_string_pretty(issues)?);
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+
+    let lines = issues.iter().map(|issue| {
+        let severity = match issue.severity {
+            Severity::Error => "error".red(),
+            Severity::Warning => "warning".yellow(),
+        };
+        let location = match issue.page {
+            Some(page) => format!("{} (page {page})", issue.slide),
+            None => issue.slide.clone(),
+        };
+        format!("{severity}[{}] {location}: {}", issue.rule, issue.message)
+    });
+
+    Ok(lines
+        .chain(std::iter::once(format!(
+            "{errors} error(s), {warnings} warning(s)"
+        )))
+        .join("\n"))
+}
+
+fn lint_config(
+    project: &Project,
+    slide: &Slide,
+    report: &mut impl FnMut(Severity, &'static str, Option<usize>, String),
+) {
+    let published = PublishedSlide::from_slide(project, slide);
+    if published.public && !published.draft && published.description.trim().is_empty() {
+        report(
+            Severity::Warning,
+            "empty-description",
+            None,
+            "public slide has an empty description".to_string(),
+        );
+    }
+
+    for alias in alias_stems(slide).iter().unique() {
+        let collisions = project
+            .slides
+            .iter()
+            .filter(|other| other.dir != slide.dir)
+            .filter(|other| alias_stems(other).contains(alias) || canonical_stem(other) == *alias)
+            .map(|other| display_path(project, &other.dir))
+            .collect::<Vec<_>>();
+
+        if !collisions.is_empty() {
+            report(
+                Severity::Error,
+                "custom-path-collision",
+                None,
+                format!(
+                    "custom_path `{alias}` collides with {}",
+                    collisions.join(", ")
+                ),
+            );
+        }
+    }
+}
+
+fn lint_contents(
+    project: &Project,
+    slide: &Slide,
+    contents: &SlideContents,
+    themes: &HashSet<String>,
+    report: &mut impl FnMut(Severity, &'static str, Option<usize>, String),
+) {
+    if let Some(theme) = contents.frontmatter_value("theme") {
+        if !themes.contains(theme) {
+            report(
+                Severity::Error,
+                "unknown-theme",
+                None,
+                format!(
+                    "theme `{theme}` is not found in {}",
+                    project.conf.build.theme_dir
+                ),
+            );
+        }
+    }
+
+    let slide_entries = slide.conf.bibliography.as_deref().unwrap_or(&[]);
+    let bib_entries = project.bibliography(slide).unwrap_or_else(|e| {
+        report(
+            Severity::Error,
+            "invalid-bibliography",
+            None,
+            format!("{e:#}"),
+        );
+        slide_entries.to_vec()
+    });
+    let mut cited = HashSet::new();
+    let max_image_size = project.conf.lint.max_image_size_kb * 1024;
+
+    for (page, page_id) in contents.pages.iter().zip(1..) {
+        for url in missing_image_refs(slide, page.contents()) {
+            report(
+                Severity::Error,
+                "missing-image",
+                Some(page_id),
+                format!("image `{url}` does not exist"),
+            );
+        }
+
+        for usage in scan_image_usages(page.contents()) {
+            if is_missing_alt(usage.alt.as_deref()) {
+                report(
+                    Severity::Warning,
+                    "missing-alt",
+                    Some(page_id),
+                    format!("image `{}` has no alt text", usage.url),
+                );
+            }
+
+            let size = usage
+                .is_local()
+                .then(|| fs::metadata(usage.local_path(&slide.dir)).ok())
+                .flatten()
+                .map(|metadata| metadata.len());
+            if let Some(size) = size.filter(|&size| size > max_image_size) {
+                report(
+                    Severity::Warning,
+                    "oversized-image",
+                    Some(page_id),
+                    format!(
+                        "image `{}` is {} KiB (limit: {} KiB)",
+                        usage.url,
+                        size / 1024,
+                        project.conf.lint.max_image_size_kb
+                    ),
+                );
+            }
+        }
+
+        for key in page.citation_keys() {
+            if bib_entries.iter().any(|entry| entry.tag == key) {
+                cited.insert(key.to_string());
+            } else {
+                report(
+                    Severity::Error,
+                    "unknown-citation",
+                    Some(page_id),
+                    format!("citation `{key}` has no bibliography entry"),
+                );
+            }
+        }
+    }
+
+    match contents.generate_label_index() {
+        Ok(label_index) => {
+            for (page, page_id) in contents.pages.iter().zip(1..) {
+                for label in page.cross_references() {
+                    if !label_index.contains_key(label) {
+                        report(
+                            Severity::Error,
+                            "unresolved-reference",
+                            Some(page_id),
+                            format!("label `{label}` is not defined"),
+                        );
+                    }
+                }
+            }
+        }
+        Err(e) => report(Severity::Error, "duplicate-label", None, e.to_string()),
+    }
+
+    match contents.generate_figure_index() {
+        Ok(figure_index) => {
+            for (page, page_id) in contents.pages.iter().zip(1..) {
+                for anchor in page.figure_references() {
+                    if !figure_index.contains_key(&anchor) {
+                        report(
+                            Severity::Error,
+                            "unresolved-figure",
+                            Some(page_id),
+                            format!("figure `{anchor}` is not defined"),
+                        );
+                    }
+                }
+            }
+        }
+        Err(e) => report(Severity::Error, "duplicate-figure", None, e.to_string()),
+    }
+
+    for entry in slide_entries {
+        if !cited.contains(&entry.tag) {
+            report(
+                Severity::Warning,
+                "unused-bibliography",
+                None,
+                format!("bibliography entry `{}` is never cited", entry.tag),
+            );
+        }
+    }
+
+    for (title, pages) in duplicate_titles(project, slide, contents) {
+        report(
+            Severity::Warning,
+            "duplicate-title",
+            None,
+            format!(
+                "title `{title}` is used on separate pages: {}",
+                pages.iter().join(", ")
+            ),
+        );
+    }
+}
+
+/// titles used by pages which are not adjacent to each other within a section
+///
+/// Headings are found the same way as `toc` does, so generated agenda pages are skipped.
+fn duplicate_titles(
+    project: &Project,
+    slide: &Slide,
+    contents: &SlideContents,
+) -> Vec<(String, Vec<usize>)> {
+    let slide_number = Regex::new(r"\s*\(\d+/\d+\)$").unwrap();
+
+    let mut runs: HashMap<(usize, String), Vec<usize>> = HashMap::new();
+    let mut section = 0;
+    let mut last_title = None;
+
+    for heading in slide_headings(slide, contents, &project.conf.toc) {
+        match heading.kind {
+            HeadingKind::Section => {
+                section += 1;
+                last_title = None;
+            }
+            HeadingKind::Title => {
+                let title = slide_number.replace(&heading.text, "").to_string();
+                if last_title.as_ref() != Some(&title) {
+                    runs.entry((section, title.clone()))
+                        .or_default()
+                        .push(heading.page + 1);
+                }
+                last_title = Some(title);
+            }
+            HeadingKind::Subheading(_) => {}
+        }
+    }
+
+    runs.into_iter()
+        .filter(|(_, pages)| pages.len() > 1)
+        .sorted()
+        .map(|((_, title), pages)| (title, pages))
+        .collect()
+}
+
+fn is_missing_alt(alt: Option<&str>) -> bool {
+    let Some(alt) = alt else {
+        return true;
+    };
+
+    let words = alt.split_whitespace().collect::<Vec<_>>();
+
+    // background images are decorative
+    if words.contains(&"bg") {
+        return false;
+    }
+
+    words
+        .iter()
+        .all(|word| word.contains(':') || word.ends_with('%') || MARP_IMAGE_KEYWORDS.contains(word))
+}
+
+/// theme names available for `theme:` in frontmatter
+fn known_themes(project: &Project) -> HashSet<String> {
+    let theme_name = Regex::new(r"@theme\s+([\w-]+)").unwrap();
+    let mut themes = BUILTIN_THEMES
+        .iter()
+        .map(|theme| theme.to_string())
+        .collect::<HashSet<_>>();
+
+    let Ok(entries) = fs::read_dir(project.root_dir.join(&project.conf.build.theme_dir)) else {
+        return themes;
+    };
+
+    for path in entries.filter_map(|e| e.ok()).map(|entry| entry.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("css") {
+            continue;
+        }
+
+        let declared = fs::read_to_string(&path)
+            .ok()
+            .and_then(|css| theme_name.captures(&css).map(|cap| cap[1].to_string()));
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string());
+
+        themes.extend(declared.into_iter().chain(stem));
+    }
+
+    themes
+}
+
+fn display_path(project: &Project, path: &Path) -> String {
+    path.strip_prefix(&project.root_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::SlideType,
+        parser::ReportFormat,
+        project::Project,
+        subcommand::{add::add, init::init},
+    };
+
+    use super::{collect_issues, render, Severity};
+
+    fn rules(issues: &[super::LintIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.rule).collect()
+    }
+
+    #[test]
+    fn test_lint_reports_broken_references() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), true, false, SlideType::Marp).unwrap();
+        std::fs::write(root.join("src/intro/images/found.png"), "png").unwrap();
+        std::fs::write(
+            root.join("src/intro/slide.md"),
+            r#"---
+marp: true
+theme: missing
+---
+
+# Title
+
+![](images/missing.png)
+![figure](images/found.png)
+
+---
+
+# Another
+
+See [1](#nowhere).
+
+---
+
+# Title
+"#,
+        )
+        .unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let issues = collect_issues(&project, &project.slides);
+        let rules = rules(&issues);
+
+        assert!(rules.contains(&"unknown-theme"));
+        assert!(rules.contains(&"missing-image"));
+        assert!(rules.contains(&"missing-alt"));
+        assert!(rules.contains(&"unknown-citation"));
+        assert!(rules.contains(&"duplicate-title"));
+        assert!(!rules.contains(&"empty-description"));
+
+        let missing = issues
+            .iter()
+            .find(|issue| issue.rule == "missing-image")
+            .unwrap();
+        assert_eq!(missing.page, Some(1));
+        assert_eq!(missing.severity, Severity::Error);
+
+        let json = render(&issues, ReportFormat::Json).unwrap();
+        assert!(json.contains(r#""rule": "unknown-citation""#));
+    }
+
+    #[test]
+    fn test_lint_skips_agenda_pages_and_titles_of_other_sections() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+        let conf_path = root.join("src/intro/slide.toml");
+        let mut conf: crate::config::SlideConf =
+            toml::from_str(&std::fs::read_to_string(&conf_path).unwrap()).unwrap();
+        conf.title_prefix = Some("## ".to_string());
+        conf.section_prefix = Some("# ".to_string());
+        std::fs::write(&conf_path, toml::to_string(&conf).unwrap()).unwrap();
+        std::fs::write(
+            root.join("src/intro/slide.md"),
+            "---\nmarp: true\n---\n\n<!-- agenda -->\n\n## Agenda\n\n---\n\n# Part 1\n\n## Summary\n\n---\n\n<!-- agenda -->\n\n## Agenda\n\n---\n\n# Part 2\n\n## Summary\n\n---\n\n## Details\n\n---\n\n## Summary\n",
+        )
+        .unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let issues = collect_issues(&project, &project.slides);
+        let duplicates = issues
+            .iter()
+            .filter(|issue| issue.rule == "duplicate-title")
+            .map(|issue| issue.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            duplicates,
+            vec!["title `Summary` is used on separate pages: 4, 6"]
+        );
+    }
+
+    #[test]
+    fn test_lint_reports_config_problems() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "alpha".to_string(), false, false, SlideType::Marp).unwrap();
+        add(&project, "beta".to_string(), false, false, SlideType::Marp).unwrap();
+        let conf_path = root.join("src/beta/slide.toml");
+        let conf = std::fs::read_to_string(&conf_path).unwrap();
+        std::fs::write(
+            &conf_path,
+            conf.replace("custom_path = []", "custom_path = [\"alpha\"]"),
+        )
+        .unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let issues = collect_issues(&project, &project.slides);
+
+        assert!(issues
+            .iter()
+            .any(|issue| issue.rule == "custom-path-collision" && issue.slide == "src/beta"));
+        assert_eq!(
+            issues
+                .iter()
+                .filter(|issue| issue.rule == "empty-description")
+                .count(),
+            2
+        );
+    }
+}
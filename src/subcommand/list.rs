@@ -6,15 +6,13 @@ pub fn list(project: &Project) -> anyhow::Result<()> {
 }
 
 fn render(project: &Project) -> String {
-    let rows = std::iter::once([
-        "no".to_string(),
-        "name".to_string(),
-        "version".to_string(),
-        "type".to_string(),
-        "draft".to_string(),
-    ])
+    let rows = std::iter::once(
+        ["no", "name", "version", "type", "draft"]
+            .map(String::from)
+            .to_vec(),
+    )
     .chain(project.slides.iter().enumerate().map(|(idx, slide)| {
-        [
+        vec![
             (idx + 1).to_string(),
             slide.conf.name.clone(),
             slide.conf.version.to_string(),
@@ -24,10 +22,17 @@ fn render(project: &Project) -> String {
     }))
     .collect::<Vec<_>>();
 
-    let widths = (0..5)
+    render_table(&rows)
+}
+
+/// render rows as a table aligned by display width
+pub fn render_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths = (0..columns)
         .map(|idx| {
             rows.iter()
-                .map(|row| display_width(&row[idx]))
+                .filter_map(|row| row.get(idx))
+                .map(|value| display_width(value))
                 .max()
                 .unwrap_or(0)
         })
@@ -1,10 +1,13 @@
 pub mod add;
 pub mod bib;
 pub mod build;
+pub mod compare;
 pub mod index;
 pub mod init;
+pub mod lint;
 pub mod list;
 pub mod migrate;
 pub mod pre_commit;
 pub mod slide;
+pub mod stats;
 pub mod version;
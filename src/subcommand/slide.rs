@@ -82,6 +82,12 @@ fn render(project: &Project, slide: &Slide) -> anyhow::Result<String> {
 
     for version in versions {
         lines.push(format!("  v{}:", version.conf.version));
+        if let Some(presentation) = version.conf.presentation() {
+            lines.push(format!("    presented: {presentation}"));
+        }
+        if let Some(notes) = &version.conf.notes {
+            lines.push(format!("    notes: {notes}"));
+        }
         let plan = PublishPlan::for_slide(project, &version);
         let primary_alias = plan.alias_stems.first();
 
@@ -91,7 +97,7 @@ fn render(project: &Project, slide: &Slide) -> anyhow::Result<String> {
                     for url in plan
                         .html_stems
                         .iter()
-                        .map(|stem| join_url(&project.conf.base_url, &stem))
+                        .map(|stem| join_url(&project.conf.base_url, stem))
                     {
                         lines.push(format!("    html: {url}"));
                     }
@@ -99,7 +105,7 @@ fn render(project: &Project, slide: &Slide) -> anyhow::Result<String> {
                     for url in plan
                         .latest_pdf_aliases
                         .iter()
-                        .map(|file| join_url(&project.conf.base_url, &file))
+                        .map(|file| join_url(&project.conf.base_url, file))
                     {
                         lines.push(format!("    pdf_latest: {url}"));
                     }
@@ -190,7 +196,11 @@ mod tests {
     use crate::{
         config::SlideType,
         project::Project,
-        subcommand::{add::add, init::init, version::bump},
+        subcommand::{
+            add::add,
+            init::init,
+            version::{bump, Presentation},
+        },
     };
 
     use super::{render, resolve_selector};
@@ -232,6 +242,11 @@ mod tests {
         bump(
             &Project::get(root.to_path_buf()).unwrap(),
             PathBuf::from("src/intro"),
+            Presentation {
+                presented_at: Some("2026-10-18".to_string()),
+                event: Some("RustConf".to_string()),
+                notes: Some("first talk".to_string()),
+            },
         )
         .unwrap();
 
@@ -246,7 +261,9 @@ mod tests {
 
         assert!(output.contains("no: 1"));
         assert!(output.contains("path: src/intro"));
-        assert!(output.contains("  v1:"));
+        assert!(
+            output.contains("  v1:\n    presented: RustConf (2026-10-18)\n    notes: first talk")
+        );
         assert!(output.contains("    pdf: https://slides.example.com/base/talks/pdf/v1/"));
         assert!(output.contains("  v2:"));
         assert!(output.contains("    html: https://slides.example.com/base/talks"));
@@ -280,6 +297,7 @@ mod tests {
         bump(
             &Project::get(root.to_path_buf()).unwrap(),
             PathBuf::from("src/intro"),
+            Default::default(),
         )
         .unwrap();
 
@@ -0,0 +1,345 @@
+//! statistics of slides
+
+use anyhow::bail;
+use regex::Regex;
+
+use crate::{
+    config::{BibEntry, StatsConf},
+    contents::SlideContents,
+    images::scan_image_usages,
+    project::Project,
+    slide::Slide,
+    subcommand::list::render_table,
+};
+
+/// statistics of a page
+#[derive(Debug, Clone, Default)]
+pub struct PageStats {
+    /// page title
+    pub title: Option<String>,
+    /// space separated words
+    pub words: usize,
+    /// CJK characters (counted separately from words)
+    pub cjk_characters: usize,
+    /// characters without whitespace
+    pub characters: usize,
+    /// number of images
+    pub images: usize,
+    /// number of fenced code blocks
+    pub code_blocks: usize,
+    /// number of cited bibliography entries
+    pub citations: usize,
+    /// speaking time in seconds
+    pub seconds: u64,
+    /// whether `seconds` comes from `<!-- time: ... -->`
+    pub annotated: bool,
+}
+
+/// statistics of a slide
+#[derive(Debug, Clone)]
+pub struct SlideStats {
+    /// slide name
+    pub name: String,
+    /// statistics of each page
+    pub pages: Vec<PageStats>,
+}
+
+impl SlideStats {
+    /// sum of all pages
+    pub fn total(&self) -> PageStats {
+        self.pages
+            .iter()
+            .fold(PageStats::default(), |acc, page| PageStats {
+                title: None,
+                words: acc.words + page.words,
+                cjk_characters: acc.cjk_characters + page.cjk_characters,
+                characters: acc.characters + page.characters,
+                images: acc.images + page.images,
+                code_blocks: acc.code_blocks + page.code_blocks,
+                citations: acc.citations + page.citations,
+                seconds: acc.seconds + page.seconds,
+                annotated: acc.annotated || page.annotated,
+            })
+    }
+}
+
+/// print statistics of one slide per page, or of several slides as a table
+pub fn stats(
+    project: &Project,
+    slides: &[Slide],
+    per_page: bool,
+    rates: &StatsConf,
+) -> anyhow::Result<()> {
+    let stats = slides
+        .iter()
+        .filter(|slide| slide.conf.type_.is_marp())
+        .map(|slide| slide_stats(project, slide, rates))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if stats.is_empty() {
+        bail!("No Marp slides to analyze");
+    }
+
+    if per_page {
+        for slide_stats in &stats {
+            println!("{}", render_pages(slide_stats));
+        }
+    } else {
+        println!("{}", render_slides(&stats));
+    }
+
+    log::info!(
+        "time is estimated with {} words/min and {} characters/min (* = annotated)",
+        rates.words_per_minute,
+        rates.characters_per_minute
+    );
+
+    Ok(())
+}
+
+/// compute statistics of a slide
+pub fn slide_stats(
+    project: &Project,
+    slide: &Slide,
+    rates: &StatsConf,
+) -> anyhow::Result<SlideStats> {
+    let contents = slide.get_contents()?;
+    let bib_entries = project.bibliography(slide)?;
+    Ok(contents_stats(slide, &contents, &bib_entries, rates))
+}
+
+fn contents_stats(
+    slide: &Slide,
+    contents: &SlideContents,
+    bib_entries: &[BibEntry],
+    rates: &StatsConf,
+) -> SlideStats {
+    let title_prefix = slide.conf.title_prefix.as_deref().unwrap_or("# ");
+
+    // backup pages after `<!-- appendix -->` are not presented
+    let pages = contents
+        .main_pages()
+        .iter()
+        .map(|page| {
+            let text = page.spoken_text();
+            let (words, cjk_characters) = count_words(&text);
+            let estimated = estimate_seconds(words, cjk_characters, rates);
+            let annotated = page.annotation("time").and_then(parse_duration);
+
+            PageStats {
+                title: page.title(title_prefix).map(String::from),
+                words,
+                cjk_characters,
+                characters: text.chars().filter(|c| !c.is_whitespace()).count(),
+                images: scan_image_usages(page.contents()).len(),
+                code_blocks: page.code_block_count(),
+                citations: page.enumerate_references(bib_entries).len(),
+                seconds: annotated.unwrap_or(estimated),
+                annotated: annotated.is_some(),
+            }
+        })
+        .collect();
+
+    SlideStats {
+        name: slide.conf.name.clone(),
+        pages,
+    }
+}
+
+/// count space separated words and CJK characters
+fn count_words(text: &str) -> (usize, usize) {
+    let mut words = 0;
+    let mut cjk_characters = 0;
+
+    for token in text.split_whitespace() {
+        cjk_characters += token.chars().filter(|&c| is_cjk(c)).count();
+        if token.chars().any(|c| c.is_alphanumeric() && !is_cjk(c)) {
+            words += 1;
+        }
+    }
+
+    (words, cjk_characters)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{ac00}'..='\u{d7af}'
+    )
+}
+
+fn estimate_seconds(words: usize, cjk_characters: usize, rates: &StatsConf) -> u64 {
+    let minutes = words as f64 / rates.words_per_minute.max(1) as f64
+        + cjk_characters as f64 / rates.characters_per_minute.max(1) as f64;
+    (minutes * 60.0).ceil() as u64
+}
+
+/// parse `90s`, `2m`, `1m30s` or `90` into seconds
+fn parse_duration(value: &str) -> Option<u64> {
+    let re = Regex::new(r"^(?:(\d+)\s*m(?:in)?)?\s*(?:(\d+)\s*s(?:ec)?)?$").unwrap();
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse() {
+        return Some(seconds);
+    }
+
+    let caps = re.captures(value)?;
+    if caps.get(1).is_none() && caps.get(2).is_none() {
+        return None;
+    }
+
+    let minutes: u64 = caps.get(1).map_or(Some(0), |m| m.as_str().parse().ok())?;
+    let seconds: u64 = caps.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+    Some(minutes * 60 + seconds)
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn render_pages(stats: &SlideStats) -> String {
+    let header = [
+        "page", "title", "words", "cjk", "chars", "images", "code", "cites", "time",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    let rows = std::iter::once(header)
+        .chain(stats.pages.iter().zip(1..).map(|(page, page_id)| {
+            let mut row = page_row(page);
+            row.insert(0, page_id.to_string());
+            row
+        }))
+        .chain(std::iter::once({
+            let mut row = page_row(&stats.total());
+            row.insert(0, "total".to_string());
+            row[1] = stats.name.clone();
+            row
+        }))
+        .collect::<Vec<_>>();
+
+    render_table(&rows)
+}
+
+fn render_slides(stats: &[SlideStats]) -> String {
+    let header = [
+        "name", "pages", "words", "cjk", "chars", "images", "code", "cites", "time",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    let rows = std::iter::once(header)
+        .chain(stats.iter().map(|slide| {
+            let mut row = page_row(&slide.total());
+            row[0] = slide.name.clone();
+            row.insert(1, slide.pages.len().to_string());
+            row
+        }))
+        .collect::<Vec<_>>();
+
+    render_table(&rows)
+}
+
+fn page_row(page: &PageStats) -> Vec<String> {
+    vec![
+        page.title.clone().unwrap_or_else(|| "-".to_string()),
+        page.words.to_string(),
+        page.cjk_characters.to_string(),
+        page.characters.to_string(),
+        page.images.to_string(),
+        page.code_blocks.to_string(),
+        page.citations.to_string(),
+        format!(
+            "{}{}",
+            format_duration(page.seconds),
+            if page.annotated { "*" } else { "" }
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        config::{SlideConf, StatsConf},
+        contents::SlideContents,
+        slide::Slide,
+    };
+
+    use super::{contents_stats, parse_duration};
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s"), Some(90));
+        assert_eq!(parse_duration("90"), Some(90));
+        assert_eq!(parse_duration("2m"), Some(120));
+        assert_eq!(parse_duration("1m30s"), Some(90));
+        assert_eq!(parse_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_contents_stats_counts_each_page() {
+        let slide = Slide {
+            dir: PathBuf::from("/tmp/project/src/intro"),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 1,
+                ..Default::default()
+            },
+        };
+        let contents = SlideContents::try_from(
+            r#"---
+marp: true
+---
+
+# Hello world
+
+one two three four five
+![figure](images/a.png)
+
+```rust
+fn main() {}
+```
+
+---
+
+# 日本語
+
+<!-- time: 1m30s -->
+こんにちは
+
+---
+
+<!-- appendix -->
+
+# Backup
+
+not presented
+"#,
+        )
+        .unwrap();
+        let rates = StatsConf {
+            words_per_minute: 60,
+            characters_per_minute: 60,
+        };
+
+        let stats = contents_stats(&slide, &contents, &[], &rates);
+
+        assert_eq!(stats.pages.len(), 2);
+        assert_eq!(stats.pages[0].title.as_deref(), Some("Hello world"));
+        assert_eq!(stats.pages[0].words, 7);
+        assert_eq!(stats.pages[0].images, 1);
+        assert_eq!(stats.pages[0].code_blocks, 1);
+        assert_eq!(stats.pages[0].seconds, 7);
+        assert!(!stats.pages[0].annotated);
+        assert_eq!(stats.pages[1].cjk_characters, 8);
+        assert_eq!(stats.pages[1].seconds, 90);
+        assert!(stats.pages[1].annotated);
+        assert_eq!(stats.total().seconds, 97);
+    }
+}
@@ -4,13 +4,41 @@ use std::{
 };
 
 use anyhow::{bail, Context};
+use toml_edit::{value, DocumentMut};
+
+use crate::{include::rebase_directives, project::Project, slide::Slide};
+
+/// when, where and why an archived version was presented
+#[derive(Debug, Clone, Default)]
+pub struct Presentation {
+    /// date of the talk (YYYY-MM-DD)
+    pub presented_at: Option<String>,
+    /// name of the event
+    pub event: Option<String>,
+    /// free-form notes
+    pub notes: Option<String>,
+}
 
-use crate::project::Project;
+impl Presentation {
+    pub fn is_empty(&self) -> bool {
+        self.presented_at.is_none() && self.event.is_none() && self.notes.is_none()
+    }
+}
 
-pub fn bump(project: &Project, dir: PathBuf) -> anyhow::Result<()> {
+/// archive the working copy as `v{version}/` and start the next version
+///
+/// The presentation is recorded in the archived `slide.toml`, over any values of the working
+/// copy; the new working copy starts without one. Fragments and snippet sources inside the
+/// slide directory are archived along with the slide file.
+pub fn bump(project: &Project, dir: PathBuf, presentation: Presentation) -> anyhow::Result<()> {
     if is_version_dir(&dir) {
         bail!("Please specify a slide root directory, not a version directory: {dir:?}");
     }
+    if let Some(date) = &presentation.presented_at {
+        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            bail!("Invalid presentation date: {date} (expected YYYY-MM-DD)");
+        }
+    }
 
     let slide = project.get_slide(&dir)?;
     let current_version = slide.conf.version;
@@ -33,6 +61,13 @@ pub fn bump(project: &Project, dir: PathBuf) -> anyhow::Result<()> {
 
     copy_required_file(&slide_file, &archive_dir.join(slide_file_name))?;
     copy_required_file(&conf_file, &archive_dir.join("slide.toml"))?;
+    if slide.conf.type_.is_marp() {
+        rebase_slide_file(&archive_dir.join(slide_file_name), &slide.dir, &archive_dir)?;
+        copy_local_dependencies(&slide, &archive_dir)?;
+    }
+    if !presentation.is_empty() {
+        record_presentation(&archive_dir.join("slide.toml"), &presentation)?;
+    }
 
     if images_dir.exists() {
         copy_dir_all(&images_dir, &archive_dir.join("images"))?;
@@ -55,6 +90,9 @@ pub fn bump(project: &Project, dir: PathBuf) -> anyhow::Result<()> {
     new_conf.version = current_version
         .checked_add(1)
         .context("version overflow while bumping")?;
+    new_conf.presented_at = None;
+    new_conf.event = None;
+    new_conf.notes = None;
     let conf_str = toml::to_string(&new_conf)?;
     fs::write(slide.dir.join("slide.toml"), conf_str)?;
 
@@ -64,6 +102,158 @@ pub fn bump(project: &Project, dir: PathBuf) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// how `restore` brings an archived version back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreMode {
+    /// archive the working copy and restore as the next version
+    NewVersion,
+    /// overwrite the working copy and keep its version
+    Replace,
+}
+
+/// copy the slide file, its local fragments, `images/` and content metadata of an archived
+/// version back into the working copy
+///
+/// Identity and publishing metadata (`name`, `version`, `secret`, `custom_path`, `draft` and
+/// `path_strategy`) of the working copy are kept. Archived directories are only read.
+pub fn restore(project: &Project, dir: PathBuf, from: u8, mode: RestoreMode) -> anyhow::Result<()> {
+    if is_version_dir(&dir) {
+        bail!("Please specify a slide root directory, not a version directory: {dir:?}");
+    }
+
+    let slide = project.get_slide(&dir)?;
+    if from == slide.conf.version {
+        bail!("v{from} is the working version of {}", slide.conf.name);
+    }
+
+    let archived = project.get_archived_slide(&slide, from)?;
+    let archived_file = archived.dir.join(archived.conf.type_.file_name());
+    if !archived_file.exists() {
+        bail!(
+            "Required file does not exist: {}",
+            archived_file.to_string_lossy()
+        );
+    }
+
+    let slide = match mode {
+        RestoreMode::NewVersion => {
+            bump(project, dir.clone(), Presentation::default())?;
+            project.get_slide_root(&dir)?
+        }
+        RestoreMode::Replace => slide,
+    };
+
+    // slide file
+    let slide_file = slide.dir.join(slide.conf.type_.file_name());
+    if slide_file.exists() {
+        fs::remove_file(&slide_file)?;
+    }
+    copy_required_file(
+        &archived_file,
+        &slide.dir.join(archived.conf.type_.file_name()),
+    )?;
+    if archived.conf.type_.is_marp() {
+        rebase_slide_file(
+            &slide.dir.join(archived.conf.type_.file_name()),
+            &archived.dir,
+            &slide.dir,
+        )?;
+        copy_local_dependencies(&archived, &slide.dir)?;
+    }
+
+    // images
+    let images_dir = slide.dir.join("images");
+    if images_dir.exists() {
+        fs::remove_dir_all(&images_dir)?;
+    }
+    copy_dir_all(&archived.dir.join("images"), &images_dir)?;
+    fs::create_dir_all(&images_dir)?;
+    if !images_dir.join(".gitignore").exists() {
+        fs::write(images_dir.join(".gitignore"), "")?;
+    }
+
+    // metadata of the contents
+    let mut new_conf = slide.conf.clone();
+    new_conf.description = archived.conf.description.clone();
+    new_conf.title_prefix = archived.conf.title_prefix.clone();
+    new_conf.section_prefix = archived.conf.section_prefix.clone();
+    new_conf.type_ = archived.conf.type_.clone();
+    new_conf.bibliography = archived.conf.bibliography.clone();
+    let conf_str = toml::to_string(&new_conf)?;
+    fs::write(slide.dir.join("slide.toml"), conf_str)?;
+
+    log::info!(
+        "restored: {} -> {}",
+        archived.dir.to_string_lossy(),
+        slide.dir.to_string_lossy()
+    );
+
+    Ok(())
+}
+
+/// set the given presentation fields of an archived `slide.toml`, keeping its formatting
+fn record_presentation(conf_path: &Path, presentation: &Presentation) -> anyhow::Result<()> {
+    let mut doc = fs::read_to_string(conf_path)?
+        .parse::<DocumentMut>()
+        .with_context(|| format!("Failed to parse {}", conf_path.to_string_lossy()))?;
+
+    for (key, field) in [
+        ("presented_at", &presentation.presented_at),
+        ("event", &presentation.event),
+        ("notes", &presentation.notes),
+    ] {
+        if let Some(field) = field {
+            doc[key] = value(field.as_str());
+        }
+    }
+
+    fs::write(conf_path, doc.to_string())?;
+    Ok(())
+}
+
+/// keep include and snippet paths of a slide file moved from `from_dir` to `to_dir` valid
+fn rebase_slide_file(path: &Path, from_dir: &Path, to_dir: &Path) -> anyhow::Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let rebased = rebase_directives(&contents, from_dir, to_dir);
+    if rebased != contents {
+        fs::write(path, rebased)?;
+    }
+
+    Ok(())
+}
+
+/// copy the files included from inside the slide directory (fragments and snippet sources) to
+/// the same place under `to_dir`
+///
+/// `images/` is copied on its own and other versions are never part of a version.
+fn copy_local_dependencies(slide: &Slide, to_dir: &Path) -> anyhow::Result<()> {
+    let slide_dir = slide.dir.canonicalize()?;
+    for path in slide.dependencies()? {
+        let Ok(relative) = path.strip_prefix(&slide_dir) else {
+            continue;
+        };
+        let top = relative
+            .components()
+            .next()
+            .map(|c| Path::new(c.as_os_str()));
+        if top.is_some_and(|top| top == Path::new("images") || is_version_dir(top)) {
+            continue;
+        }
+
+        let dst = to_dir.join(relative);
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&path, &dst)?;
+        if dst.extension().is_some_and(|ext| ext == "md") {
+            let from_dir = path.parent().unwrap_or(&slide_dir);
+            rebase_slide_file(&dst, from_dir, dst.parent().unwrap_or(to_dir))?;
+        }
+    }
+
+    Ok(())
+}
+
 fn copy_required_file(src: &Path, dst: &Path) -> anyhow::Result<()> {
     if !src.exists() {
         bail!("Required file does not exist: {}", src.to_string_lossy());
@@ -113,7 +303,7 @@ mod tests {
         subcommand::{add::add, init::init},
     };
 
-    use super::bump;
+    use super::{bump, restore, Presentation, RestoreMode};
 
     #[test]
     fn test_bump_archives_and_increments_version() {
@@ -126,7 +316,12 @@ mod tests {
         std::fs::write(root.join("src/intro/slide.md"), "# before bump").unwrap();
 
         let project = Project::get(root.to_path_buf()).unwrap();
-        bump(&project, PathBuf::from("src/intro")).unwrap();
+        bump(
+            &project,
+            PathBuf::from("src/intro"),
+            Presentation::default(),
+        )
+        .unwrap();
 
         let archived_md = root.join("src/intro/v1/slide.md");
         assert!(archived_md.exists());
@@ -144,6 +339,104 @@ mod tests {
         assert!(root.join("src/intro/images/.gitignore").exists());
     }
 
+    #[test]
+    fn test_bump_records_presentation_in_archive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+        let conf_path = root.join("src/intro/slide.toml");
+        let conf_str = std::fs::read_to_string(&conf_path).unwrap();
+        std::fs::write(&conf_path, format!("event = \"Meetup\"\n{conf_str}")).unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let invalid = Presentation {
+            presented_at: Some("18/10/2026".to_string()),
+            ..Default::default()
+        };
+        assert!(bump(&project, PathBuf::from("src/intro"), invalid).is_err());
+        assert!(!root.join("src/intro/v1").exists());
+
+        let presentation = Presentation {
+            presented_at: Some("2026-10-18".to_string()),
+            event: Some("RustConf".to_string()),
+            notes: None,
+        };
+        bump(&project, PathBuf::from("src/intro"), presentation).unwrap();
+
+        let read_conf = |path: &str| -> crate::config::SlideConf {
+            toml::from_str(&std::fs::read_to_string(root.join(path)).unwrap()).unwrap()
+        };
+        let archived = read_conf("src/intro/v1/slide.toml");
+        assert_eq!(archived.version, 1);
+        assert_eq!(archived.presented_at.as_deref(), Some("2026-10-18"));
+        assert_eq!(archived.event.as_deref(), Some("RustConf"));
+        assert_eq!(archived.notes, None);
+
+        let working = read_conf("src/intro/slide.toml");
+        assert_eq!(working.version, 2);
+        assert_eq!(working.presentation(), None);
+        assert_eq!(working.notes, None);
+    }
+
+    #[test]
+    fn test_bump_keeps_includes_of_archived_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+        std::fs::create_dir_all(root.join("shared")).unwrap();
+        std::fs::write(root.join("shared/about.md"), "# About me\n").unwrap();
+        std::fs::create_dir_all(root.join("src/intro/parts")).unwrap();
+        std::fs::write(
+            root.join("src/intro/parts/method.md"),
+            "# Method\n\nv1\n\n<!-- include: ../../../shared/about.md -->\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/intro/slide.md"),
+            "---\nmarp: true\n---\n\n<!-- include: ../../shared/about.md -->\n\n---\n\n<!-- include: parts/method.md -->\n",
+        )
+        .unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        bump(
+            &project,
+            PathBuf::from("src/intro"),
+            Presentation::default(),
+        )
+        .unwrap();
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide(&PathBuf::from("src/intro")).unwrap();
+        let archived = project.get_archived_slide(&slide, 1).unwrap();
+        std::fs::write(root.join("src/intro/parts/method.md"), "# Method\n\nv2\n").unwrap();
+        let expanded = archived.get_expanded_source().unwrap();
+        assert!(expanded.contains("# About me"));
+        assert!(expanded.contains("v1"));
+        assert_eq!(expanded.matches("# About me").count(), 2);
+
+        restore(
+            &project,
+            PathBuf::from("src/intro"),
+            1,
+            RestoreMode::Replace,
+        )
+        .unwrap();
+        let slide = project.get_slide(&PathBuf::from("src/intro")).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(slide.dir.join("slide.md")).unwrap(),
+            "---\nmarp: true\n---\n\n<!-- include: ../../shared/about.md -->\n\n---\n\n<!-- include: parts/method.md -->\n"
+        );
+        assert!(std::fs::read_to_string(slide.dir.join("parts/method.md"))
+            .unwrap()
+            .contains("v1"));
+    }
+
     #[test]
     fn test_bump_fails_if_archive_already_exists() {
         let tmp = tempfile::tempdir().unwrap();
@@ -155,7 +448,89 @@ mod tests {
         std::fs::create_dir_all(root.join("src/intro/v1")).unwrap();
 
         let project = Project::get(root.to_path_buf()).unwrap();
-        let res = bump(&project, PathBuf::from("src/intro"));
+        let res = bump(
+            &project,
+            PathBuf::from("src/intro"),
+            Presentation::default(),
+        );
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_restore_archived_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let slide_dir = root.join("src/intro");
+
+        init(root).unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        add(&project, "intro".to_string(), false, false, SlideType::Marp).unwrap();
+        std::fs::write(slide_dir.join("slide.md"), "# first talk").unwrap();
+        std::fs::write(slide_dir.join("images/a.png"), "a").unwrap();
+        let project = Project::get(root.to_path_buf()).unwrap();
+        bump(
+            &project,
+            PathBuf::from("src/intro"),
+            Presentation::default(),
+        )
+        .unwrap();
+        std::fs::write(slide_dir.join("slide.md"), "# second talk").unwrap();
+        std::fs::write(slide_dir.join("images/b.png"), "b").unwrap();
+
+        // as a new version
+        let project = Project::get(root.to_path_buf()).unwrap();
+        restore(
+            &project,
+            PathBuf::from("src/intro"),
+            1,
+            RestoreMode::NewVersion,
+        )
+        .unwrap();
+
+        let read = |path: &str| std::fs::read_to_string(slide_dir.join(path)).unwrap();
+        assert_eq!(read("slide.md"), "# first talk");
+        assert_eq!(read("images/a.png"), "a");
+        assert!(!slide_dir.join("images/b.png").exists());
+        assert_eq!(read("v1/slide.md"), "# first talk");
+        assert_eq!(read("v2/slide.md"), "# second talk");
+        assert_eq!(read("v2/images/b.png"), "b");
+
+        let project = Project::get(root.to_path_buf()).unwrap();
+        let slide = project.get_slide(&slide_dir).unwrap();
+        assert_eq!(slide.conf.version, 3);
+        let versions = project
+            .get_archived_slides(&slide)
+            .unwrap()
+            .into_iter()
+            .map(|slide| slide.conf.version)
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec![1, 2]);
+
+        // replacing the working copy
+        restore(
+            &project,
+            PathBuf::from("src/intro"),
+            2,
+            RestoreMode::Replace,
+        )
+        .unwrap();
+        assert_eq!(read("slide.md"), "# second talk");
+        let project = Project::get(root.to_path_buf()).unwrap();
+        assert_eq!(project.get_slide(&slide_dir).unwrap().conf.version, 3);
+
+        assert!(restore(
+            &project,
+            PathBuf::from("src/intro"),
+            3,
+            RestoreMode::Replace
+        )
+        .is_err());
+        assert!(restore(
+            &project,
+            PathBuf::from("src/intro"),
+            9,
+            RestoreMode::Replace
+        )
+        .is_err());
+    }
 }
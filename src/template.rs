@@ -3,7 +3,7 @@
 use askama::Template;
 
 use crate::{
-    config::{PathStrategy, ProjectConf},
+    config::{PathStrategy, ProjectConf, SlideConf},
     path::PublishPlan,
     project::Project,
     slide::Slide,
@@ -20,6 +20,8 @@ pub struct PublishedSlide {
     pub slide_version_paths: Vec<String>,
     pub pdf_path: String,
     pub pdf_version_paths: Vec<String>,
+    /// presentations of versions (e.g. `v1: RustConf (2026-10-18) — first talk`)
+    pub version_notes: Vec<String>,
 }
 
 impl PublishedSlide {
@@ -59,6 +61,13 @@ impl PublishedSlide {
                 PathStrategy::CanonicalWithRedirects => format!("{primary_stem}/pdf/v{version}/"),
             })
             .collect();
+        let version_notes = project
+            .get_archived_slides(slide)
+            .unwrap_or_default()
+            .iter()
+            .chain(std::iter::once(slide))
+            .filter_map(|version| version_note(&version.conf))
+            .collect();
 
         Self {
             name: slide.conf.name.clone(),
@@ -70,10 +79,28 @@ impl PublishedSlide {
             slide_version_paths,
             pdf_path,
             pdf_version_paths,
+            version_notes,
         }
     }
 }
 
+/// `v1: RustConf (2026-10-18) — first talk` of a version with a recorded presentation
+fn version_note(conf: &SlideConf) -> Option<String> {
+    let details = conf
+        .presentation()
+        .into_iter()
+        .chain(conf.notes.clone())
+        .collect::<Vec<_>>();
+    (!details.is_empty()).then(|| format!("v{}: {}", conf.version, details.join(" — ")))
+}
+
+mod filters {
+    /// escape `|`, which would end a cell of a Markdown table
+    pub fn escape_pipe<T: std::fmt::Display>(s: T) -> askama::Result<String> {
+        Ok(s.to_string().replace('|', "\\|"))
+    }
+}
+
 #[derive(Template)]
 #[template(path = "index.html")]
 pub struct IndexTemplate<'a> {
@@ -107,6 +134,7 @@ mod test_template {
                 slide_version_paths: vec!["title1/v1/".to_string()],
                 pdf_path: "title1.pdf".to_string(),
                 pdf_version_paths: vec!["title1_v1.pdf".to_string()],
+                version_notes: vec!["v1: RustConf (2026-10-18)".to_string()],
             },
             PublishedSlide {
                 name: "title2".to_string(),
@@ -118,6 +146,7 @@ mod test_template {
                 slide_version_paths: vec![],
                 pdf_path: "uuid.pdf".to_string(),
                 pdf_version_paths: vec!["uuid_v1.pdf".to_string()],
+                version_notes: vec![],
             },
             PublishedSlide {
                 name: "title3".to_string(),
@@ -129,6 +158,7 @@ mod test_template {
                 slide_version_paths: vec![],
                 pdf_path: "path.pdf".to_string(),
                 pdf_version_paths: vec!["path_v1.pdf".to_string()],
+                version_notes: vec![],
             },
             PublishedSlide {
                 name: "title4".to_string(),
@@ -140,12 +170,14 @@ mod test_template {
                 slide_version_paths: vec![],
                 pdf_path: "title4.pdf".to_string(),
                 pdf_version_paths: vec!["title4_v1.pdf".to_string()],
+                version_notes: vec![],
             },
         ];
         let template = IndexTemplate { slides: &slides };
 
         let result = template.render().expect("Failed to format");
         eprintln!("{result}");
+        assert!(result.contains(r#"<div class="slide-date">v1: RustConf (2026-10-18)</div>"#));
     }
 
     #[test]
@@ -169,7 +201,7 @@ mod test_template {
             output_dir: "output".to_string(),
             template,
             build: build_conf,
-            images: Default::default(),
+            ..Default::default()
         };
 
         let slides = vec![
@@ -183,6 +215,7 @@ mod test_template {
                 slide_version_paths: vec!["title1/v1/".to_string()],
                 pdf_path: "title1.pdf".to_string(),
                 pdf_version_paths: vec!["title1_v1.pdf".to_string()],
+                version_notes: vec!["v1: RustConf (2026-10-18) — first talk".to_string()],
             },
             PublishedSlide {
                 name: "title2".to_string(),
@@ -194,6 +227,7 @@ mod test_template {
                 slide_version_paths: vec![],
                 pdf_path: "uuid.pdf".to_string(),
                 pdf_version_paths: vec!["uuid_v1.pdf".to_string()],
+                version_notes: vec![],
             },
             PublishedSlide {
                 name: "title3".to_string(),
@@ -205,6 +239,7 @@ mod test_template {
                 slide_version_paths: vec![],
                 pdf_path: "path.pdf".to_string(),
                 pdf_version_paths: vec!["path_v1.pdf".to_string()],
+                version_notes: vec![],
             },
             PublishedSlide {
                 name: "title4".to_string(),
@@ -216,6 +251,7 @@ mod test_template {
                 slide_version_paths: vec![],
                 pdf_path: "title4.pdf".to_string(),
                 pdf_version_paths: vec!["title4_v1.pdf".to_string()],
+                version_notes: vec!["v1: Meetup — A | B\nsecond line".to_string()],
             },
         ];
 
@@ -231,9 +267,9 @@ mod test_template {
 
 | Title | Slide | PDF | Description |
 | :---- | :---: | :-: | :---------- |
-| title1 | [Slide](https://test.dev/slides/title1),[v1](https://test.dev/slides/title1/v1/) | [PDF](https://test.dev/slides/title1.pdf),[v1](https://test.dev/slides/title1_v1.pdf) |  |
+| title1 | [Slide](https://test.dev/slides/title1),[v1](https://test.dev/slides/title1/v1/) | [PDF](https://test.dev/slides/title1.pdf),[v1](https://test.dev/slides/title1_v1.pdf) | v1: RustConf (2026-10-18) — first talk |
 | title2 |  -  | [PDF](https://test.dev/slides/uuid.pdf),[v1](https://test.dev/slides/uuid_v1.pdf) |  |
 | title3 | - | - |  |
-| title4 | [Slide](https://test.dev/slides/title4) | [PDF](https://test.dev/slides/title4.pdf),[v1](https://test.dev/slides/title4_v1.pdf) | タイトル4 |"));
+| title4 | [Slide](https://test.dev/slides/title4) | [PDF](https://test.dev/slides/title4.pdf),[v1](https://test.dev/slides/title4_v1.pdf) | タイトル4<br>v1: Meetup — A \\| B<br/>second line |"));
     }
 }
@@ -0,0 +1,199 @@
+//! placeholders (`{{ slide.name }}`) in slide sources and templates
+
+use std::collections::BTreeMap;
+
+use regex::{Captures, Regex};
+
+use crate::{
+    config::PathStrategy, contents::Fences, path::canonical_stem, project::Project, slide::Slide,
+};
+
+/// values of placeholders available to a slide
+#[derive(Debug, Clone)]
+pub struct Variables {
+    values: BTreeMap<&'static str, String>,
+}
+
+impl Variables {
+    /// variables of `slide` in `project`
+    pub fn new(project: &Project, slide: &Slide) -> Self {
+        let base_url = project.conf.base_url.trim_end_matches('/');
+        let stem = canonical_stem(slide);
+        let (canonical, pdf) = match project.path_strategy(slide) {
+            PathStrategy::Legacy => (format!("{stem}/"), format!("{stem}.pdf")),
+            PathStrategy::CanonicalWithRedirects => (format!("{stem}/"), format!("{stem}/pdf/")),
+        };
+
+        let values = BTreeMap::from([
+            ("project.name", project.conf.name.clone()),
+            ("project.author", project.conf.author.clone()),
+            ("slide.name", slide.conf.name.clone()),
+            ("slide.version", slide.conf.version.to_string()),
+            (
+                "slide.description",
+                slide.conf.description.clone().unwrap_or_default(),
+            ),
+            ("date", chrono::Local::now().format("%Y-%m-%d").to_string()),
+            ("url.canonical", format!("{base_url}/{canonical}")),
+            ("url.pdf", format!("{base_url}/{pdf}")),
+        ]);
+
+        Self { values }
+    }
+
+    /// value of a placeholder
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// replace known placeholders outside fenced code blocks and inline code spans
+    ///
+    /// Unknown placeholders are left as they are.
+    pub fn expand(&self, text: &str) -> String {
+        let placeholder = Regex::new(r"\{\{\s*([\w.]+)\s*\}\}").unwrap();
+        let mut fences = Fences::default();
+
+        let mut expanded = text
+            .lines()
+            .map(|line| {
+                fences.toggles(line);
+                if fences.is_open() {
+                    return line.to_string();
+                }
+
+                split_code_spans(line)
+                    .into_iter()
+                    .map(|(part, is_code)| {
+                        if is_code {
+                            return part.to_string();
+                        }
+                        placeholder
+                            .replace_all(part, |caps: &Captures| {
+                                self.get(&caps[1])
+                                    .map(String::from)
+                                    .unwrap_or_else(|| caps[0].to_string())
+                            })
+                            .to_string()
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if text.ends_with('\n') {
+            expanded.push('\n');
+        }
+
+        expanded
+    }
+}
+
+/// split a line into text and inline code spans (`true`), including their backticks
+///
+/// A span closes at the next run of as many backticks; an unmatched run is plain text.
+fn split_code_spans(line: &str) -> Vec<(&str, bool)> {
+    let backticks = |from: usize| line[from..].len() - line[from..].trim_start_matches('`').len();
+    let mut parts = vec![];
+    let mut text_start = 0;
+    let mut pos = 0;
+
+    while let Some(offset) = line[pos..].find('`') {
+        let open = pos + offset;
+        let run = backticks(open);
+        pos = open + run;
+
+        let mut search = pos;
+        while let Some(offset) = line[search..].find('`') {
+            let start = search + offset;
+            let len = backticks(start);
+            search = start + len;
+            if len == run {
+                if text_start < open {
+                    parts.push((&line[text_start..open], false));
+                }
+                parts.push((&line[open..search], true));
+                text_start = search;
+                pos = search;
+                break;
+            }
+        }
+    }
+
+    if text_start < line.len() {
+        parts.push((&line[text_start..], false));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        config::{PathStrategy, ProjectConf, SlideConf},
+        project::Project,
+        slide::Slide,
+    };
+
+    use super::Variables;
+
+    #[test]
+    fn test_expand_variables() {
+        let slide = Slide {
+            dir: PathBuf::from("/tmp/project/src/intro"),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 3,
+                custom_path: Some(vec!["hello".to_string()]),
+                path_strategy: Some(PathStrategy::CanonicalWithRedirects),
+                ..Default::default()
+            },
+        };
+        let project = Project {
+            root_dir: PathBuf::from("/tmp/project"),
+            conf: ProjectConf {
+                author: "Alice".to_string(),
+                base_url: "https://example.com/slides/".to_string(),
+                ..Default::default()
+            },
+            slides: vec![slide.clone()],
+        };
+        let variables = Variables::new(&project, &slide);
+
+        let expanded = variables.expand(
+            "# {{ slide.name }} v{{slide.version}}\n\nby {{ project.author }}\n\n{{ url.canonical }} / {{ url.pdf }}\n\n```\n{{ slide.name }}\n```\n\n{{ unknown }}\n",
+        );
+
+        assert_eq!(
+            expanded,
+            "# intro v3\n\nby Alice\n\nhttps://example.com/slides/intro/ / https://example.com/slides/intro/pdf/\n\n```\n{{ slide.name }}\n```\n\n{{ unknown }}\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_skips_inline_code_spans() {
+        let slide = Slide {
+            dir: PathBuf::from("/tmp/project/src/intro"),
+            conf: SlideConf {
+                name: "intro".to_string(),
+                version: 3,
+                ..Default::default()
+            },
+        };
+        let project = Project {
+            root_dir: PathBuf::from("/tmp/project"),
+            conf: ProjectConf::default(),
+            slides: vec![slide.clone()],
+        };
+        let variables = Variables::new(&project, &slide);
+
+        let expanded = variables.expand(
+            "Write `{{ slide.name }}` or ``{{ `slide.version` }}`` for {{ slide.name }}\n` {{ slide.version }}\n",
+        );
+
+        assert_eq!(
+            expanded,
+            "Write `{{ slide.name }}` or ``{{ `slide.version` }}`` for intro\n` 3\n"
+        );
+    }
+}